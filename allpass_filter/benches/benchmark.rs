@@ -1,5 +1,16 @@
-﻿use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use allpass_filter::{AllPassFilter, Linear, Cubic};
+﻿use criterion::{black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use criterion::measurement::WallTime;
+use allpass_filter::{AllPassFilter, Linear, Nearest, Cubic, Lagrange};
+
+/// N次ラグランジュ補間のベンチマークを登録
+fn bench_lagrange<const N: usize>(group: &mut BenchmarkGroup<WallTime>) {
+    group.bench_function(format!("Lagrange Interpolation (order {})", N), |b| {
+        let mut allpass_filter = AllPassFilter::new(1000, 10.5, 0.5, Lagrange::<N>);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+}
 
 fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("Interpolation Comparison");
@@ -18,6 +29,14 @@ fn criterion_benchmark(c: &mut Criterion) {
         })
     });
 
+    bench_lagrange::<1>(&mut group);
+    bench_lagrange::<2>(&mut group);
+    bench_lagrange::<3>(&mut group);
+    bench_lagrange::<4>(&mut group);
+    bench_lagrange::<5>(&mut group);
+    bench_lagrange::<6>(&mut group);
+    bench_lagrange::<7>(&mut group);
+
     group.finish();
 
     let mut group_block = c.benchmark_group("Block Processing Comparison");
@@ -34,6 +53,59 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 
     group_block.finish();
+
+    let mut group_wrap = c.benchmark_group("Ring Buffer Wrapping Comparison");
+
+    group_wrap.bench_function("Modulo Wrapping with Linear Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new(1024, 10.5, 0.5, Linear);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.bench_function("Bitmask Wrapping with Linear Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new_pow2(1024, 10.5, 0.5, Linear);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.bench_function("Modulo Wrapping with Cubic Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new(1024, 10.5, 0.5, Cubic);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.bench_function("Bitmask Wrapping with Cubic Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new_pow2(1024, 10.5, 0.5, Cubic);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.bench_function("Mirrored Buffer with Cubic Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new_mirrored(1024, 10.5, 0.5, Cubic);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.bench_function("Modulo Wrapping with Nearest Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new(1024, 10.5, 0.5, Nearest);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.bench_function("Bitmask Wrapping with Nearest Interpolation", |b| {
+        let mut allpass_filter = AllPassFilter::new_pow2(1024, 10.5, 0.5, Nearest);
+        b.iter(|| {
+            allpass_filter.process(black_box(1.0));
+        })
+    });
+
+    group_wrap.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,52 +1,295 @@
 ﻿use num_traits::Float;
-use crate::delay::DelayLine;
-use crate::interpolation::{Interpolator, Linear};
-use crate::parameter::SmoothedParam;
-
-pub struct AllPassFilter<T, I> {
-    delay_line: DelayLine<T, I>,    // 遅延線
-    delay_length: SmoothedParam<T>, // 遅延時間（サンプル単位）
-    g: SmoothedParam<T>,            // フィードバックゲイン
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use crate::delay::{DelayBuffer, DelayLine};
+#[cfg(feature = "alloc")]
+use crate::delay::DelayLineSnapshot;
+use crate::error::Error;
+use crate::event::{ParamEvent, ParamTarget};
+use crate::interpolation::StatefulInterpolator;
+#[cfg(feature = "alloc")]
+use crate::interpolation::{Linear, DynamicInterpolator, InterpolationMode};
+use crate::lfo::Lfo;
+#[cfg(feature = "alloc")]
+use crate::lfo::LfoState;
+#[cfg(all(feature = "alloc", target_has_atomic = "32", target_has_atomic = "ptr"))]
+use crate::params::AllPassParams;
+use crate::parameter::{SmoothedParam, SmoothingStyle, DEFAULT_SMOOTHING_TIME_MS};
+#[cfg(feature = "alloc")]
+use crate::parameter::SmoothedParamSnapshot;
+use crate::processor::Processor;
+use crate::stability::GainGuard;
+#[cfg(feature = "alloc")]
+use crate::stability::{GainPolicy, DEFAULT_MAX_GAIN};
+use crate::time::{DelayTime, DEFAULT_SAMPLE_RATE};
+
+#[cfg(feature = "alloc")]
+pub struct AllPassFilter<T, I, S = Vec<T>> {
+    delay_line: DelayLine<T, I, S>,       // 遅延線
+    delay_length: SmoothedParam<T>,       // 遅延時間（サンプル単位）
+    g: SmoothedParam<T>,                  // フィードバックゲイン
+    gain_guard: GainGuard<T>,             // ゲインの安定化ガード
+    delay_time: DelayTime<T>,             // 単位付きの遅延時間
+    max_delay_time: Option<DelayTime<T>>, // 時間単位の最大遅延（サンプル数で作成した場合はNone）
+    smoothing_time: Option<T>,            // 平滑化の時定数（ミリ秒、係数で指定した場合はNone）
+    sample_rate: T,                       // サンプルレート（Hz）
+    lfo: Option<Lfo<T>>,                  // 遅延時間の変調（無効の場合はNone）
+    #[cfg(all(target_has_atomic = "32", target_has_atomic = "ptr"))]
+    params_gain: Option<T>,               // `apply_params`で最後に読み込んだゲイン
+}
+
+/// allocが無効な場合は遅延バッファの型を省略できない（`StaticAllPassFilter`を使う）
+#[cfg(not(feature = "alloc"))]
+pub struct AllPassFilter<T, I, S> {
+    delay_line: DelayLine<T, I, S>,       // 遅延線
+    delay_length: SmoothedParam<T>,       // 遅延時間（サンプル単位）
+    g: SmoothedParam<T>,                  // フィードバックゲイン
+    gain_guard: GainGuard<T>,             // ゲインの安定化ガード
+    delay_time: DelayTime<T>,             // 単位付きの遅延時間
+    max_delay_time: Option<DelayTime<T>>, // 時間単位の最大遅延（サンプル数で作成した場合はNone）
+    smoothing_time: Option<T>,            // 平滑化の時定数（ミリ秒、係数で指定した場合はNone）
+    sample_rate: T,                       // サンプルレート（Hz）
+    lfo: Option<Lfo<T>>,                  // 遅延時間の変調（無効の場合はNone）
+}
+
+/// `AllPassFilter`の内部状態
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg(feature = "alloc")]
+pub struct AllPassFilterSnapshot<T> {
+    pub delay_line: DelayLineSnapshot<T>,       // 遅延線の内容
+    pub delay_length: SmoothedParamSnapshot<T>, // 遅延時間の平滑化状態
+    pub g: SmoothedParamSnapshot<T>,            // フィードバックゲインの平滑化状態
+    pub lfo: Option<LfoState<T>>,               // LFOの位相と乱数の状態（変調なしの場合はNone）
 }
 
+/// 固定長配列を遅延バッファに使うAllPassFilter（アロケータ不要）
+pub type StaticAllPassFilter<T, I, const N: usize> = AllPassFilter<T, I, [T; N]>;
+
+/// ブロック処理でパラメータを先読みする単位（サンプル数）
+const BLOCK_CHUNK: usize = 64;
+
+#[cfg(feature = "alloc")]
 impl<T: Float> AllPassFilter<T, Linear> {
     pub fn new_default(max_delay_samples: usize, initial_delay: T, gain: T) -> Self {
         Self::new(max_delay_samples, initial_delay, gain, Linear)
     }
 }
 
-impl<T: Float, I: Interpolator<T>> AllPassFilter<T, I> {
-    /// 新しいAllPassFilterを作成
+#[cfg(feature = "alloc")]
+impl<T: Float> AllPassFilter<T, DynamicInterpolator<T>> {
+    /// 補間方法を実行時に切り替え可能なAllPassFilterを作成
     /// `max_delay_samples`: 最大遅延サンプル数
     /// `initial_delay`: 初期遅延時間（サンプル単位）
     /// `gain`: フィードバックゲイン
+    /// `mode`: 初期の補間方法
+    pub fn new_dynamic(max_delay_samples: usize, initial_delay: T, gain: T, mode: InterpolationMode) -> Self {
+        Self::new(max_delay_samples, initial_delay, gain, DynamicInterpolator::new(mode))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float, S: DelayBuffer<T>> AllPassFilter<T, DynamicInterpolator<T>, S> {
+    /// 補間方法を切り替える（クロスフェード付き）
+    /// 遅延時間は新しい補間方法の`min_delay()`から`max_delay()`の範囲に収め直す
+    /// `mode`: 新しい補間方法
+    pub fn set_interpolation_mode(&mut self, mode: InterpolationMode) {
+        self.delay_line.interpolator_mut().set_mode(mode);
+        self.delay_length.set_target(self.delay_samples());
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float, I: StatefulInterpolator<T>> AllPassFilter<T, I> {
+    /// 新しいAllPassFilterを作成
+    /// `max_delay_samples`: 最大遅延サンプル数
+    /// `initial_delay`: 初期遅延時間（サンプル単位）
+    /// `gain`: フィードバックゲイン（安定範囲にクランプされ、NaNは0になる）
     /// `interpolator`: 補間方法
     pub fn new(max_delay_samples: usize, initial_delay: T, gain: T, interpolator: I) -> Self {
-        let default_smooth = T::from(0.01).unwrap();
+        Self::from_delay_line(DelayLine::new(max_delay_samples, interpolator), initial_delay, gain)
+    }
+
+    /// 遅延バッファ長を2の累乗に切り上げたAllPassFilterを作成
+    /// リングバッファの折り返しがビットマスクになり、剰余演算を避けられる
+    /// `max_delay_samples`: 最大遅延サンプル数（2の累乗に切り上げられる）
+    /// `initial_delay`: 初期遅延時間（サンプル単位）
+    /// `gain`: フィードバックゲイン
+    /// `interpolator`: 補間方法
+    pub fn new_pow2(max_delay_samples: usize, initial_delay: T, gain: T, interpolator: I) -> Self {
+        Self::from_delay_line(DelayLine::new_pow2(max_delay_samples, interpolator), initial_delay, gain)
+    }
+
+    /// ミラーバッファの遅延線を使うAllPassFilterを作成
+    /// 補間カーネルを連続したスライスとして読むため、長いカーネルで有利になる
+    /// `max_delay_samples`: 最大遅延サンプル数（メモリは2倍確保される）
+    /// `initial_delay`: 初期遅延時間（サンプル単位）
+    /// `gain`: フィードバックゲイン
+    /// `interpolator`: 補間方法
+    pub fn new_mirrored(max_delay_samples: usize, initial_delay: T, gain: T, interpolator: I) -> Self {
+        Self::from_delay_line(DelayLine::new_mirrored(max_delay_samples, interpolator), initial_delay, gain)
+    }
+
+    /// 時間単位の遅延でAllPassFilterを作成
+    /// `set_sample_rate`でサンプルレートが変わると遅延サンプル数を再計算する
+    /// `sample_rate`: サンプルレート（Hz）
+    /// `max_delay`: 最大遅延時間
+    /// `initial_delay`: 初期遅延時間
+    /// `gain`: フィードバックゲイン
+    /// `interpolator`: 補間方法
+    pub fn new_with_time(sample_rate: T, max_delay: DelayTime<T>, initial_delay: DelayTime<T>, gain: T, interpolator: I) -> Self {
+        let delay_line = DelayLine::new_with_time(max_delay, sample_rate, interpolator);
+        let mut allpass = Self::from_delay_line(delay_line, initial_delay.to_samples(sample_rate), gain);
+        allpass.delay_time = initial_delay;
+        allpass.max_delay_time = Some(max_delay);
+        allpass.sample_rate = sample_rate;
+        allpass.set_smoothing_time(T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap());
+        allpass
+    }
+
+    /// 最大遅延サンプル数を収められるように遅延バッファを確保する（アロケーションを伴う）
+    /// `max_delay_samples`: 確保する最大遅延サンプル数
+    pub fn reserve(&mut self, max_delay_samples: usize) {
+        self.delay_line.reserve(max_delay_samples);
+    }
+
+    /// 最大サンプルレートでの最大遅延時間を収められるように遅延バッファを確保する（アロケーションを伴う）
+    /// 以降の`set_sample_rate`はこの範囲内であればアロケーションを行わない
+    /// `max_delay`: 最大遅延時間
+    /// `max_sample_rate`: 想定する最大のサンプルレート（Hz）
+    pub fn reserve_with_time(&mut self, max_delay: DelayTime<T>, max_sample_rate: T) {
+        self.delay_line.reserve_with_time(max_delay, max_sample_rate);
+    }
+
+    /// バッファサイズ、初期遅延時間、ゲインを検証してAllPassFilterを作成
+    /// `max_delay_samples`: 最大遅延サンプル数
+    /// `initial_delay`: 初期遅延時間（サンプル単位、`min_delay()`以上`max_delay()`以下）
+    /// `gain`: フィードバックゲイン
+    /// `interpolator`: 補間方法
+    /// 戻り値: 生成したAllPassFilter（いずれかが不正な場合はエラー）
+    pub fn try_new(max_delay_samples: usize, initial_delay: T, gain: T, interpolator: I) -> Result<Self, Error> {
+        GainGuard::new(GainPolicy::Reject, T::from(DEFAULT_MAX_GAIN).unwrap()).apply(gain)?;
+
+        let delay_line = DelayLine::try_new(max_delay_samples, interpolator)?;
+        let min_delay = T::from(delay_line.min_delay()).unwrap();
+        let max_delay = T::from(delay_line.max_delay()).unwrap();
+        if !(initial_delay >= min_delay && initial_delay <= max_delay) {
+            return Err(Error::DelayOutOfRange);
+        }
+
+        Ok(Self::from_delay_line(delay_line, initial_delay, gain))
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>, const N: usize> AllPassFilter<T, I, [T; N]> {
+    /// 固定長配列を遅延バッファに使うAllPassFilterを作成（ヒープ確保を行わない）
+    /// `initial_delay`: 初期遅延時間（サンプル単位）
+    /// `gain`: フィードバックゲイン
+    /// `interpolator`: 補間方法
+    pub fn new_static(initial_delay: T, gain: T, interpolator: I) -> Self {
+        Self::from_delay_line(DelayLine::new_static(interpolator), initial_delay, gain)
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>, S: DelayBuffer<T>> AllPassFilter<T, I, S> {
+    /// 遅延線からAllPassFilterを組み立てる
+    /// 初期遅延時間は`min_delay()`から`max_delay()`の範囲に切り詰める
+    fn from_delay_line(delay_line: DelayLine<T, I, S>, initial_delay: T, gain: T) -> Self {
+        let min_delay = T::from(delay_line.min_delay()).unwrap();
+        let max_delay = T::from(delay_line.max_delay()).unwrap();
+        let initial_delay = initial_delay.max(min_delay).min(max_delay);
+        let smoothing_time = T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap();
+        let sample_rate = T::from(DEFAULT_SAMPLE_RATE).unwrap();
+        let gain_guard = GainGuard::default();
+        let gain = gain_guard.apply(gain).unwrap_or(T::zero());
 
         Self {
-            delay_line: DelayLine::new(max_delay_samples, interpolator),
-            delay_length: SmoothedParam::new(initial_delay, default_smooth),
-            g: SmoothedParam::new(gain, default_smooth),
+            delay_line,
+            delay_length: SmoothedParam::with_time_constant(initial_delay, smoothing_time, sample_rate),
+            g: SmoothedParam::with_time_constant(gain, smoothing_time, sample_rate),
+            gain_guard,
+            delay_time: DelayTime::Samples(initial_delay),
+            max_delay_time: None,
+            smoothing_time: Some(smoothing_time),
+            sample_rate,
+            lfo: None,
+            #[cfg(all(feature = "alloc", target_has_atomic = "32", target_has_atomic = "ptr"))]
+            params_gain: None,
         }
     }
 
+    /// 補間器を考慮した最大遅延時間（サンプル単位）
+    pub fn max_delay(&self) -> usize {
+        self.delay_line.max_delay()
+    }
+
+    /// 補間器の先読みを考慮した最小遅延時間（サンプル単位）
+    pub fn min_delay(&self) -> usize {
+        self.delay_line.min_delay()
+    }
+
     /// 平滑化係数を設定
     /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
     pub fn set_smoothing(&mut self, factor: T) {
+        self.smoothing_time = None;
         self.delay_length.set_factor(factor);
         self.g.set_factor(factor);
     }
 
+    /// 遅延時間とゲインの目標値への近づき方を設定
+    /// 遅延時間を`Linear`で動かすと、変化中のピッチのずれが一定になる
+    /// 時間単位のランプの長さは`set_sample_rate`でサンプルレートが変わっても同じ時間を保つ
+    /// `style`: 平滑化のスタイル
+    pub fn set_smoothing_style(&mut self, style: SmoothingStyle<T>) {
+        self.delay_length.set_style(style, self.sample_rate);
+        self.g.set_style(style, self.sample_rate);
+    }
+
+    /// 平滑化の時定数を設定
+    /// `set_sample_rate`でサンプルレートが変わっても同じ時間で目標値に近づく
+    /// `time_constant_ms`: 目標値との差が1/eになるまでの時間（ミリ秒）
+    pub fn set_smoothing_time(&mut self, time_constant_ms: T) {
+        self.smoothing_time = Some(time_constant_ms);
+        self.delay_length.set_time_constant(time_constant_ms, self.sample_rate);
+        self.g.set_time_constant(time_constant_ms, self.sample_rate);
+    }
+
+    /// 遅延時間を揺らすLFOを設定
+    /// 平滑化された遅延時間にLFOの出力を毎サンプル加える（`min_delay()`から`max_delay()`の範囲に収める）
+    /// `lfo`: 変調に使うLFO（サンプルレートはこのフィルタに合わせる。Noneで変調なし）
+    pub fn set_modulation(&mut self, lfo: Option<Lfo<T>>) {
+        self.lfo = lfo;
+        if let Some(lfo) = &mut self.lfo {
+            lfo.set_sample_rate(self.sample_rate);
+        }
+    }
+
+    /// 遅延時間を揺らすLFOを取得（周波数や振れ幅の変更に使う）
+    pub fn modulation_mut(&mut self) -> Option<&mut Lfo<T>> {
+        self.lfo.as_mut()
+    }
+
+    /// 平滑化された遅延時間にLFOのオフセットを加える
+    #[inline]
+    fn modulate(&mut self, delay: T) -> T {
+        match &mut self.lfo {
+            Some(lfo) => (delay + lfo.next_offset())
+                .max(T::from(self.delay_line.min_delay()).unwrap())
+                .min(T::from(self.delay_line.max_delay()).unwrap()),
+            None => delay,
+        }
+    }
+
     /// オーディオサンプルを処理
     /// `input`: 入力サンプル
     /// 戻り値: 出力サンプル
     #[inline]
     pub fn process(&mut self, input: T) -> T {
         let current_delay = self.delay_length.next();
+        let current_delay = self.modulate(current_delay);
         let current_g = self.g.next();
 
-        let delayed_value = self.delay_line.read_interpolated(current_delay);
+        let delayed_value = self.delay_line.read_interpolated_mut(current_delay);
         let v_n = input + (current_g * delayed_value);
         let output = delayed_value - (current_g * v_n);
 
@@ -59,36 +302,320 @@ impl<T: Float, I: Interpolator<T>> AllPassFilter<T, I> {
     /// `input`: 入力サンプルのスライス
     /// `output`: 出力サンプルのスライス
     pub fn process_block(&mut self, input: &[T], output: &mut [T]) {
-        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
-            *out_sample = self.process(*in_sample);
-        }
+        let len = input.len().min(output.len());
+        output[..len].copy_from_slice(&input[..len]);
+        self.process_block_inplace(&mut output[..len]);
     }
 
     /// ブロック単位でオーディオサンプルをインプレース処理
+    /// 遅延線の読み書きをまとめて行い、`process`を繰り返すのと同じ結果を返す
     /// `buffer`: 入出力サンプルのスライス
     pub fn process_block_inplace(&mut self, buffer: &mut [T]) {
-        for sample in buffer.iter_mut() {
-            *sample = self.process(*sample);
+        let lookahead = self.delay_line.interpolator().lookahead();
+        let mut delays = [T::zero(); BLOCK_CHUNK];
+        let mut gains = [T::zero(); BLOCK_CHUNK];
+        let mut delayed = [T::zero(); BLOCK_CHUNK];
+
+        for chunk in buffer.chunks_mut(BLOCK_CHUNK) {
+            // パラメータが動いていない間は平滑化を省き、補間係数を使い回す
+            if !self.delay_length.is_smoothing() && !self.g.is_smoothing() && self.lfo.is_none() {
+                self.process_static(chunk, lookahead, &mut delayed);
+                continue;
+            }
+
+            let len = chunk.len();
+            self.delay_length.next_block(&mut delays[..len]);
+            self.g.next_block(&mut gains[..len]);
+            if self.lfo.is_some() {
+                for delay in &mut delays[..len] {
+                    *delay = self.modulate(*delay);
+                }
+            }
+
+            let mut offset = 0;
+            while offset < len {
+                // このブロックで書き込むサンプルに届かない範囲をまとめて読む
+                let min_delay = delays[offset..len].iter().fold(T::infinity(), |acc, &d| acc.min(d));
+                let reach = min_delay.floor().to_usize().unwrap_or(0).saturating_sub(lookahead);
+                let end = offset + reach.clamp(1, len - offset);
+
+                self.delay_line.read_block(&delays[offset..end], &mut delayed[offset..end]);
+                let frames = chunk[offset..end].iter_mut().zip(&mut delayed[offset..end]).zip(&gains[offset..end]);
+                for ((sample, d), &g) in frames {
+                    let v_n = *sample + (g * *d);
+                    *sample = *d - (g * v_n);
+                    *d = v_n;
+                }
+                self.delay_line.write_block(&delayed[offset..end]);
+
+                offset = end;
+            }
         }
     }
 
+    /// 遅延時間とゲインが一定の区間を処理する
+    /// `chunk`: 入出力サンプルのスライス（`delayed`以下の長さ）
+    /// `lookahead`: 補間器の先読みサンプル数
+    /// `delayed`: 作業用バッファ
+    fn process_static(&mut self, chunk: &mut [T], lookahead: usize, delayed: &mut [T]) {
+        let delay = self.delay_length.current();
+        let g = self.g.current();
+        let reach = delay.floor().to_usize().unwrap_or(0).saturating_sub(lookahead).max(1);
+
+        for frames in chunk.chunks_mut(reach) {
+            let delayed = &mut delayed[..frames.len()];
+            self.delay_line.read_block_constant(delay, delayed);
+            for (sample, d) in frames.iter_mut().zip(delayed.iter_mut()) {
+                let v_n = *sample + (g * *d);
+                *sample = *d - (g * v_n);
+                *d = v_n;
+            }
+            self.delay_line.write_block(delayed);
+        }
+    }
+
+    /// パラメータ変更イベントをサンプル単位で反映しながらブロックを処理
+    /// `input`: 入力サンプルのスライス
+    /// `output`: 出力サンプルのスライス
+    /// `events`: `offset`の昇順に並べたイベント（`offset`はブロック長未満）
+    /// 戻り値: イベントが不正な場合は`InvalidEvent`、ゲインがガードで拒否された場合は最初のエラー
+    pub fn process_block_with_events(&mut self, input: &[T], output: &mut [T], events: &[ParamEvent<T>]) -> Result<(), Error> {
+        let len = input.len().min(output.len());
+        output[..len].copy_from_slice(&input[..len]);
+        self.process_block_inplace_with_events(&mut output[..len], events)
+    }
+
+    /// パラメータ変更イベントをサンプル単位で反映しながらブロックをインプレース処理
+    /// 各イベントは`offset`のサンプルを処理する直前に反映される
+    /// ブロック長以上の位置や昇順でないイベントがある場合は、どのイベントも反映せずにブロックを処理する
+    /// `buffer`: 入出力サンプルのスライス
+    /// `events`: `offset`の昇順に並べたイベント（`offset`はブロック長未満）
+    /// 戻り値: イベントが不正な場合は`InvalidEvent`、ゲインがガードで拒否された場合は最初のエラー（残りのイベントと処理は続行する）
+    pub fn process_block_inplace_with_events(&mut self, buffer: &mut [T], events: &[ParamEvent<T>]) -> Result<(), Error> {
+        let in_block = events.iter().all(|event| event.offset < buffer.len());
+        let in_order = events.windows(2).all(|pair| pair[0].offset <= pair[1].offset);
+        if !(in_block && in_order) {
+            self.process_block_inplace(buffer);
+            return Err(Error::InvalidEvent);
+        }
+
+        let mut result = Ok(());
+        let mut pos = 0;
+
+        for event in events {
+            let offset = event.offset;
+            self.process_block_inplace(&mut buffer[pos..offset]);
+            pos = offset;
+
+            let applied = match event.target {
+                ParamTarget::Delay(delay) => {
+                    self.set_delay_time(delay);
+                    Ok(())
+                }
+                ParamTarget::Gain(gain) => self.set_gain(gain),
+            };
+            result = result.and(applied);
+        }
+
+        self.process_block_inplace(&mut buffer[pos..]);
+        result
+    }
+
+    /// 別スレッドから書き込まれた最新の目標値を読み込む（ブロックごとに1回呼ぶ）
+    /// 未設定の値と、前回から変わっていない値はそのままにする（遅延時間は`min_delay()`から`max_delay()`の範囲に収める）
+    /// `params`: 共有パラメータ
+    /// 戻り値: 新しいゲインがガードで拒否された場合はエラー（遅延時間と平滑化係数は反映済み）
+    #[cfg(all(feature = "alloc", target_has_atomic = "32", target_has_atomic = "ptr"))]
+    pub fn apply_params(&mut self, params: &AllPassParams<T>) -> Result<(), Error> {
+        if let Some(delay) = params.delay()
+            && delay != self.delay_length.target()
+        {
+            self.set_delay(delay);
+        }
+        if let Some(factor) = params.smoothing()
+            && factor != self.delay_length.factor()
+        {
+            self.set_smoothing(factor);
+        }
+        // 拒否されたゲインを毎ブロック再適用しないよう、書き込まれた値が変わったときだけ適用する
+        match params.gain() {
+            Some(gain) if Some(gain) != self.params_gain => {
+                self.params_gain = Some(gain);
+                self.set_gain(gain)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 遅延線をゼロで埋め、パラメータを目標値に合わせる
+    pub fn reset(&mut self) {
+        self.delay_line.reset();
+        self.delay_length.reset();
+        self.g.reset();
+        if let Some(lfo) = &mut self.lfo {
+            lfo.reset();
+        }
+    }
+
+    /// 遅延線の内容、パラメータの平滑化状態、LFOの位相と乱数の状態を保存する
+    #[cfg(feature = "alloc")]
+    pub fn snapshot(&self) -> AllPassFilterSnapshot<T> {
+        AllPassFilterSnapshot {
+            delay_line: self.delay_line.snapshot(),
+            delay_length: self.delay_length.snapshot(),
+            g: self.g.snapshot(),
+            lfo: self.lfo.as_ref().map(Lfo::state),
+        }
+    }
+
+    /// 保存した内部状態を復元する（アロケーションを行わない）
+    /// `snapshot`: `snapshot`で保存した状態
+    /// 戻り値: 遅延線に復元できない、遅延時間が範囲外、ゲインがガードで拒否された、
+    /// LFOの有無が保存時と異なる場合はエラー（状態は変更しない）
+    #[cfg(feature = "alloc")]
+    pub fn restore(&mut self, snapshot: &AllPassFilterSnapshot<T>) -> Result<(), Error> {
+        // 復元後のリングバッファ長と補間器の状態で読み出せる範囲に遅延時間が収まっているか確認
+        let (headroom, lookahead) = self.delay_line.interpolator().range_for_state(&snapshot.delay_line.interpolator);
+        let min_delay = T::from(1 + lookahead).unwrap();
+        let max_delay = T::from(snapshot.delay_line.buffer.len().saturating_sub(headroom)).unwrap();
+        let delay = &snapshot.delay_length;
+        if [delay.current, delay.target, delay.start].iter().any(|&d| !(d >= min_delay && d <= max_delay)) {
+            return Err(Error::DelayOutOfRange);
+        }
+
+        // ゲインはガードを通して安定範囲に収める
+        let g = SmoothedParamSnapshot {
+            current: self.gain_guard.apply(snapshot.g.current)?,
+            target: self.gain_guard.apply(snapshot.g.target)?,
+            start: self.gain_guard.apply(snapshot.g.start)?,
+            ramp_pos: snapshot.g.ramp_pos,
+        };
+
+        // LFOの状態は複製で検証し、遅延線の復元が成功してから反映する
+        let mut lfo = self.lfo;
+        match (&mut lfo, &snapshot.lfo) {
+            (Some(lfo), Some(state)) => lfo.restore_state(state)?,
+            (None, None) => {}
+            _ => return Err(Error::InvalidSnapshot),
+        }
+
+        self.delay_line.restore(&snapshot.delay_line)?;
+        self.delay_length.restore(&snapshot.delay_length);
+        self.g.restore(&g);
+        self.lfo = lfo;
+        Ok(())
+    }
+
     /// 遅延時間を設定
+    /// `min_delay()`から`max_delay()`の範囲に切り詰める
     /// `delay`: 遅延時間（サンプル単位）
     pub fn set_delay(&mut self, delay: T) {
-        self.delay_length.set_target(delay);
+        self.delay_time = DelayTime::Samples(delay);
+        self.delay_length.set_target(self.delay_samples());
+    }
+
+    /// 単位付きの遅延時間を設定
+    /// 現在のサンプルレートでサンプル数に変換し、`min_delay()`から`max_delay()`の範囲に切り詰める
+    /// `delay`: 遅延時間
+    pub fn set_delay_time(&mut self, delay: DelayTime<T>) {
+        self.delay_time = delay;
+        self.delay_length.set_target(self.delay_samples());
+    }
+
+    /// 単位付きの遅延時間を取得
+    pub fn delay_time(&self) -> DelayTime<T> {
+        self.delay_time
+    }
+
+    /// 確保済みの領域内で遅延バッファ長を変更する（アロケーションを行わない）
+    /// `max_delay_samples`: 新しい遅延バッファ長
+    /// 戻り値: 確保済みの長さを超える場合はエラー
+    pub fn resize(&mut self, max_delay_samples: usize) -> Result<(), Error> {
+        self.delay_line.resize(max_delay_samples)?;
+        self.max_delay_time = None;
+        self.delay_length.set_immediate(self.delay_samples());
+        Ok(())
+    }
+
+    /// サンプルレートを設定
+    /// サンプル数以外で指定された遅延は平滑化を経ずに新しいサンプル数へ切り替わる
+    /// 時間単位の最大遅延で作成した場合は、確保済みの範囲内で遅延バッファ長も合わせる
+    /// サンプル数で指定された遅延も、縮んだバッファに収まらない場合は`max_delay()`に切り詰める
+    /// `sample_rate`: サンプルレート（Hz）
+    pub fn set_sample_rate(&mut self, sample_rate: T) {
+        self.sample_rate = sample_rate;
+        if let Some(max_delay) = self.max_delay_time
+            && self.delay_line.resize_with_time(max_delay, sample_rate).is_err()
+        {
+            let _ = self.delay_line.resize(self.delay_line.capacity());
+        }
+        let max_delay = T::from(self.max_delay()).unwrap();
+        if !matches!(self.delay_time, DelayTime::Samples(_))
+            || self.delay_length.current() > max_delay
+            || self.delay_length.target() > max_delay
+        {
+            self.delay_length.set_immediate(self.delay_samples());
+        }
+        if let Some(time_constant_ms) = self.smoothing_time {
+            self.set_smoothing_time(time_constant_ms);
+        }
+        self.delay_length.set_ramp_sample_rate(sample_rate);
+        self.g.set_ramp_sample_rate(sample_rate);
+        if let Some(lfo) = &mut self.lfo {
+            lfo.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// サンプルレートを取得
+    pub fn sample_rate(&self) -> T {
+        self.sample_rate
+    }
+
+    /// 現在のサンプルレートでの遅延サンプル数（`min_delay()`から`max_delay()`の範囲に切り詰める）
+    fn delay_samples(&self) -> T {
+        let min_delay = T::from(self.min_delay()).unwrap();
+        let max_delay = T::from(self.max_delay()).unwrap();
+        self.delay_time.to_samples(self.sample_rate).max(min_delay).min(max_delay)
     }
 
     /// フィードバックゲインを設定
+    /// ガードのポリシーに従って安定範囲に収めた値を目標値にする
     /// `gain`: フィードバックゲイン
-    pub fn set_gain(&mut self, gain: T) {
+    /// 戻り値: Rejectで範囲外の場合はエラー（目標値は変更しない）
+    pub fn set_gain(&mut self, gain: T) -> Result<(), Error> {
+        let gain = self.gain_guard.apply(gain)?;
         self.g.set_target(gain);
+        Ok(())
+    }
+
+    /// ゲインの安定化ガードを設定
+    /// `gain_guard`: 以降の`set_gain`に適用するガード
+    pub fn set_gain_guard(&mut self, gain_guard: GainGuard<T>) {
+        self.gain_guard = gain_guard;
+    }
+
+    /// ゲインの安定化ガードを取得
+    pub fn gain_guard(&self) -> GainGuard<T> {
+        self.gain_guard
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>, S: DelayBuffer<T>> Processor<T> for AllPassFilter<T, I, S> {
+    #[inline]
+    fn process(&mut self, input: T) -> T {
+        AllPassFilter::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        AllPassFilter::reset(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::interpolation::Interpolator;
+    use crate::interpolation::{Cubic, Lagrange, Nearest, Sinc, Thiran, DEFAULT_CROSSFADE_SAMPLES};
+    use crate::lfo::LfoShape;
 
     #[test]
     fn test_delay_behaviour_at_zero_gain() {
@@ -120,4 +647,659 @@ mod tests {
             assert!((out_single - output_block[i]).abs() < 1e-6, "Block and single sample outputs should match");
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_thiran_matches_linear_at_integer_delay() {
+        let mut allpass_filter_linear = AllPassFilter::new(100, 8.0, 0.5, Linear);
+        let mut allpass_filter_thiran = AllPassFilter::new(100, 8.0, 0.5, Thiran::new());
+
+        for i in 0..64 {
+            let input = if i == 0 { 1.0 } else { 0.0 };
+            let out_linear = allpass_filter_linear.process(input);
+            let out_thiran = allpass_filter_thiran.process(input);
+            assert!((out_linear - out_thiran).abs() < 1e-9, "Thiran should equal Linear at integer delay");
+        }
+    }
+
+    #[test]
+    fn test_thiran_magnitude_is_flat_at_fractional_delay() {
+        /// 正弦波を遅延させたときの定常状態の振幅比（RMS比）
+        fn gain_at<I: StatefulInterpolator<f64>>(interpolator: I, freq: f64) -> f64 {
+            let mut allpass_filter = AllPassFilter::new(100, 10.3, 0.0, interpolator);
+            let (mut input_energy, mut output_energy) = (0.0, 0.0);
+            for n in 0..4000 {
+                let input = (core::f64::consts::TAU * freq * n as f64).sin();
+                let output = allpass_filter.process(input);
+                if n >= 1000 {
+                    input_energy += input * input;
+                    output_energy += output * output;
+                }
+            }
+            (output_energy / input_energy).sqrt()
+        }
+
+        for freq in [0.05, 0.2, 0.35, 0.45] {
+            let gain = gain_at(Thiran::new(), freq);
+            assert!((gain - 1.0).abs() < 1e-2, "Thiran magnitude should be flat at {freq} (got {gain})");
+        }
+
+        // 同じ小数遅延で線形補間は高域が減衰する
+        assert!(gain_at(Linear, 0.45) < 0.9);
+    }
+
+    #[test]
+    fn test_first_order_lagrange_matches_linear() {
+        let mut allpass_filter_linear = AllPassFilter::new(100, 7.3, 0.5, Linear);
+        let mut allpass_filter_lagrange = AllPassFilter::new(100, 7.3, 0.5, Lagrange::<1>);
+
+        for i in 0..64 {
+            let input = if i == 0 { 1.0 } else { 0.0 };
+            let out_linear = allpass_filter_linear.process(input);
+            let out_lagrange = allpass_filter_lagrange.process(input);
+            assert!((out_linear - out_lagrange).abs() < 1e-9, "First-order Lagrange should equal Linear");
+        }
+    }
+
+    #[test]
+    fn test_lagrange_accuracy_improves_with_order() {
+        /// 小数遅延の正弦波と理想的な遅延正弦波との定常状態のRMS誤差
+        fn error_at<I: StatefulInterpolator<f64>>(interpolator: I, freq: f64) -> f64 {
+            let delay = 10.3;
+            let mut allpass_filter = AllPassFilter::new(100, delay, 0.0, interpolator);
+            let (mut error_energy, mut count) = (0.0, 0.0);
+            for n in 0..4000 {
+                let input = (core::f64::consts::TAU * freq * n as f64).sin();
+                let output = allpass_filter.process(input);
+                if n >= 1000 {
+                    let ideal = (core::f64::consts::TAU * freq * (n as f64 - delay)).sin();
+                    error_energy += (output - ideal) * (output - ideal);
+                    count += 1.0;
+                }
+            }
+            (error_energy / count).sqrt()
+        }
+
+        // 次数が上がるほど小数遅延の誤差が小さくなる
+        for freq in [0.05, 0.1, 0.2] {
+            let (linear, lagrange3, lagrange5) = (error_at(Linear, freq), error_at(Lagrange::<3>, freq), error_at(Lagrange::<5>, freq));
+            assert!(lagrange3 < linear && lagrange5 < lagrange3, "Higher Lagrange orders should be more accurate at {freq}");
+        }
+
+        assert!(error_at(Lagrange::<3>, 0.1) < 5e-3);
+        assert!(error_at(Lagrange::<5>, 0.1) < 5e-4);
+    }
+
+    #[test]
+    fn test_sinc_matches_linear_at_integer_delay() {
+        let mut allpass_filter_linear = AllPassFilter::new(100, 12.0, 0.5, Linear);
+        let mut allpass_filter_sinc = AllPassFilter::new(100, 12.0, 0.5, Sinc::default());
+
+        for i in 0..64 {
+            let input = if i == 0 { 1.0 } else { 0.0 };
+            let out_linear = allpass_filter_linear.process(input);
+            let out_sinc = allpass_filter_sinc.process(input);
+            assert!((out_linear - out_sinc).abs() < 1e-9, "Sinc should equal Linear at integer delay");
+        }
+    }
+
+    #[test]
+    fn test_sinc_is_band_limited_at_fractional_delay() {
+        /// 0.4 fsの正弦波を小数遅延させたときの定常状態の振幅比と、理想的な遅延正弦波とのRMS誤差
+        fn response<I: StatefulInterpolator<f64>>(interpolator: I) -> (f64, f64) {
+            let (delay, freq) = (20.5, 0.4);
+            let mut allpass_filter = AllPassFilter::new(100, delay, 0.0, interpolator);
+            let (mut input_energy, mut output_energy, mut error_energy) = (0.0, 0.0, 0.0);
+            for n in 0..4000 {
+                let input = (core::f64::consts::TAU * freq * n as f64).sin();
+                let output = allpass_filter.process(input);
+                if n >= 1000 {
+                    let ideal = (core::f64::consts::TAU * freq * (n as f64 - delay)).sin();
+                    input_energy += input * input;
+                    output_energy += output * output;
+                    error_energy += (output - ideal) * (output - ideal);
+                }
+            }
+            ((output_energy / input_energy).sqrt(), (error_energy / input_energy).sqrt())
+        }
+
+        // 半サンプルの遅延で線形補間は0.4 fsを大きく減衰させるが、Sincはほぼ平坦
+        let (sinc_gain, sinc_error) = response(Sinc::default());
+        let (linear_gain, linear_error) = response(Linear);
+        assert!(sinc_gain > 0.9, "Sinc should keep 0.4 fs nearly flat (got {sinc_gain})");
+        assert!(linear_gain < 0.5);
+
+        // 誤差は振幅の減衰分とほぼ等しく、位相は理想的な遅延と揃っている
+        assert!(sinc_error < (1.0 - sinc_gain) + 0.01, "Sinc phase should match the ideal delay (error {sinc_error})");
+        assert!(sinc_error < linear_error * 0.2);
+    }
+
+    #[test]
+    fn test_dynamic_interpolator_crossfades_to_new_mode() {
+        // ゲイン0では純粋な遅延になるため、出力は補間値そのものになる
+        let mut allpass_filter_dynamic = AllPassFilter::new(100, 6.5, 0.0, DynamicInterpolator::with_crossfade(InterpolationMode::Linear, 16));
+        let mut allpass_filter_linear = AllPassFilter::new(100, 6.5, 0.0, Linear);
+        let mut allpass_filter_cubic = AllPassFilter::new(100, 6.5, 0.0, Cubic);
+
+        for i in 0..80 {
+            // 遅延線に信号が入っている状態で切り替える
+            if i == 40 {
+                allpass_filter_dynamic.set_interpolation_mode(InterpolationMode::Cubic);
+            }
+
+            let input = (0.3 * core::f64::consts::TAU * i as f64).sin();
+            let out_dynamic = allpass_filter_dynamic.process(input);
+            let out_linear = allpass_filter_linear.process(input);
+            let out_cubic = allpass_filter_cubic.process(input);
+
+            let expected = match i {
+                ..40 => out_linear,
+                40..56 => {
+                    // クロスフェード中は旧方式から新方式へ直線的に移る
+                    let ratio = (i - 40) as f64 / 16.0;
+                    out_linear + (out_cubic - out_linear) * ratio
+                }
+                _ => out_cubic,
+            };
+            assert!((out_dynamic - expected).abs() < 1e-9, "Dynamic interpolator should crossfade at sample {i}");
+            if i == 48 {
+                assert!((out_dynamic - out_linear).abs() > 1e-3 && (out_dynamic - out_cubic).abs() > 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dynamic_interpolator_range_follows_active_mode() {
+        // Linearで使う間はLinearと同じ遅延範囲になる
+        let mut allpass_filter_dynamic = AllPassFilter::new_dynamic(1000, 5.0, 0.5, InterpolationMode::Linear);
+        let allpass_filter_linear = AllPassFilter::new(1000, 5.0, 0.5, Linear);
+        assert_eq!(allpass_filter_dynamic.min_delay(), allpass_filter_linear.min_delay());
+        assert_eq!(allpass_filter_dynamic.max_delay(), allpass_filter_linear.max_delay());
+        allpass_filter_dynamic.set_delay(5.0);
+        assert_eq!(allpass_filter_dynamic.delay_length.target(), 5.0);
+
+        // Sincに切り替えるとSincの先読みに合わせて遅延時間を収め直す
+        allpass_filter_dynamic.set_interpolation_mode(InterpolationMode::Sinc);
+        assert_eq!(allpass_filter_dynamic.min_delay(), 9);
+        assert_eq!(allpass_filter_dynamic.delay_length.target(), 9.0);
+
+        // クロスフェードが終わるまでは切り替え元の範囲も含む
+        allpass_filter_dynamic.set_interpolation_mode(InterpolationMode::Linear);
+        assert_eq!(allpass_filter_dynamic.min_delay(), 9);
+        for _ in 0..DEFAULT_CROSSFADE_SAMPLES {
+            allpass_filter_dynamic.process(0.0);
+        }
+        assert_eq!(allpass_filter_dynamic.min_delay(), 2);
+    }
+
+    #[test]
+    fn test_gain_guard_policies() {
+        let mut allpass_filter = AllPassFilter::new_default(100, 10.0, 0.5);
+
+        allpass_filter.set_gain_guard(GainGuard::new(GainPolicy::Reject, 0.9));
+        assert_eq!(allpass_filter.set_gain(1.5), Err(Error::UnstableGain));
+        assert_eq!(allpass_filter.set_gain(f64::NAN), Err(Error::UnstableGain));
+        assert_eq!(allpass_filter.set_gain(0.8), Ok(()));
+
+        assert_eq!(GainGuard::new(GainPolicy::Clamp, 0.9).apply(-1.5), Ok(-0.9));
+        let soft = GainGuard::new(GainPolicy::SoftLimit, 0.9).apply(1.5).unwrap();
+        assert!(soft < 0.9 && soft > 0.8);
+
+        // 上限自体が安定範囲外のガードは作れない
+        assert_eq!(GainGuard::try_new(GainPolicy::Clamp, 1.5).err(), Some(Error::UnstableGain));
+        assert!(GainGuard::try_new(GainPolicy::Clamp, 0.0).is_err());
+        assert!(GainGuard::try_new(GainPolicy::Clamp, f64::NAN).is_err());
+
+        assert!(AllPassFilter::try_new(100, 10.0, 1.0, Linear).is_err());
+        assert!(AllPassFilter::try_new(100, 10.0, 0.7, Linear).is_ok());
+    }
+
+    #[test]
+    fn test_try_new_validation() {
+        assert_eq!(AllPassFilter::try_new(0, 2.0, 0.5, Linear).err(), Some(Error::InvalidBufferSize));
+        assert_eq!(AllPassFilter::try_new(1, 2.0, 0.5, Cubic).err(), Some(Error::InvalidBufferSize));
+        assert_eq!(AllPassFilter::try_new(100, 100.5, 0.5, Linear).err(), Some(Error::DelayOutOfRange));
+        assert_eq!(AllPassFilter::try_new(100, -1.0, 0.5, Linear).err(), Some(Error::DelayOutOfRange));
+
+        // Cubicは1サンプル分のヘッドルームが必要
+        assert_eq!(AllPassFilter::new(100, 10.0, 0.5, Linear).max_delay(), 100);
+        assert_eq!(AllPassFilter::new(100, 10.0, 0.5, Cubic).max_delay(), 99);
+        assert!(AllPassFilter::try_new(100, 100.0, 0.5, Cubic).is_err());
+        assert!(AllPassFilter::try_new(100, 99.0, 0.5, Cubic).is_ok());
+
+        // 先読みする補間器では、書き込み位置に届く短い遅延を拒否する
+        assert_eq!(AllPassFilter::new(100, 10.0, 0.5, Cubic).min_delay(), 3);
+        assert_eq!(AllPassFilter::try_new(100, 0.0, 0.5, Linear).err(), Some(Error::DelayOutOfRange));
+        assert_eq!(AllPassFilter::try_new(100, 2.5, 0.5, Cubic).err(), Some(Error::DelayOutOfRange));
+        assert!(AllPassFilter::try_new(100, 3.0, 0.5, Cubic).is_ok());
+        assert!(AllPassFilter::try_new(100, 2.9, 0.5, Thiran::new()).is_err());
+    }
+
+    #[test]
+    fn test_set_delay_is_clamped() {
+        let mut allpass_filter_block = AllPassFilter::new(64, 10.0, 0.5, Cubic);
+        let mut allpass_filter_single = AllPassFilter::new(64, 10.0, 0.5, Cubic);
+        for allpass_filter in [&mut allpass_filter_block, &mut allpass_filter_single] {
+            allpass_filter.set_smoothing(0.2);
+            allpass_filter.set_delay(500.0);
+        }
+        assert_eq!(allpass_filter_block.delay_length.target(), 63.0);
+
+        // バッファ長を超える遅延を指定してもブロック処理と1サンプルずつの処理が一致する
+        let input: Vec<f64> = (0..300).map(|i| if i % 70 == 0 { 1.0 } else { 0.0 }).collect();
+        let mut output = vec![0.0; 300];
+        allpass_filter_block.process_block(&input, &mut output);
+        for (&x, &y) in input.iter().zip(&output) {
+            assert!((allpass_filter_single.process(x) - y).abs() < 1e-12);
+        }
+
+        allpass_filter_block.set_delay(-4.0);
+        assert_eq!(allpass_filter_block.delay_length.target(), 3.0);
+
+        // コンストラクタの初期遅延時間も同じ範囲に収める
+        assert_eq!(AllPassFilter::new(64, 500.0, 0.5, Cubic).delay_length.current(), 63.0);
+        assert_eq!(AllPassFilter::new(64, 0.5, 0.5, Cubic).delay_length.current(), 3.0);
+        assert_eq!(AllPassFilter::<f64, Cubic, [f64; 64]>::new_static(500.0, 0.5, Cubic).delay_length.current(), 63.0);
+
+        // サンプルレートの変更でバッファが縮んだ場合は、サンプル数で指定した遅延も収め直す
+        let mut allpass_filter = AllPassFilter::new_with_time(4000.0, DelayTime::Millis(20.0), DelayTime::Millis(10.0), 0.5, Linear);
+        allpass_filter.set_delay(70.0);
+        allpass_filter.set_sample_rate(1000.0);
+        assert_eq!(allpass_filter.max_delay(), 20);
+        assert_eq!(allpass_filter.delay_length.current(), 20.0);
+        assert_eq!(allpass_filter.delay_length.target(), 20.0);
+    }
+
+    #[test]
+    fn test_f32_delay_smoothing_finishes() {
+        // f32でも平滑化が終わり、静的な高速経路に戻る
+        let mut allpass_filter: AllPassFilter<f32, Linear> = AllPassFilter::new(1000, 10.0, 0.5, Linear);
+        allpass_filter.set_delay(300.0);
+        let mut buffer = [0.0f32; 512];
+        for _ in 0..20 {
+            allpass_filter.process_block_inplace(&mut buffer);
+        }
+        assert!(!allpass_filter.delay_length.is_smoothing());
+        assert_eq!(allpass_filter.delay_length.current(), 300.0);
+    }
+
+    #[test]
+    fn test_pow2_matches_modulo() {
+        for initial_delay in [3.0, 17.25, 30.5] {
+            let mut allpass_filter_modulo = AllPassFilter::new(32, initial_delay, 0.5, Cubic);
+            let mut allpass_filter_pow2 = AllPassFilter::new_pow2(20, initial_delay, 0.5, Cubic);
+            assert_eq!(allpass_filter_pow2.max_delay(), 31);
+
+            for i in 0..200 {
+                let input = if i % 40 == 0 { 1.0 } else { 0.0 };
+                let out_modulo = allpass_filter_modulo.process(input);
+                let out_pow2 = allpass_filter_pow2.process(input);
+                assert!((out_modulo - out_pow2).abs() < 1e-12, "Power-of-two buffer should match modulo buffer");
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirrored_matches_modulo() {
+        fn assert_matches<I: StatefulInterpolator<f64>>(make: impl Fn() -> I) {
+            for initial_delay in [9.0, 17.25, 30.5, 31.75] {
+                let mut allpass_filter_modulo = AllPassFilter::new(40, initial_delay, 0.5, make());
+                let mut allpass_filter_mirrored = AllPassFilter::new_mirrored(40, initial_delay, 0.5, make());
+
+                for i in 0..200 {
+                    let input = if i % 45 == 0 { 1.0 } else { 0.0 };
+                    let out_modulo = allpass_filter_modulo.process(input);
+                    let out_mirrored = allpass_filter_mirrored.process(input);
+                    assert!((out_modulo - out_mirrored).abs() < 1e-12, "Mirrored buffer should match modulo buffer");
+                }
+            }
+        }
+
+        assert_matches(|| Linear);
+        assert_matches(|| Nearest);
+        assert_matches(|| Cubic);
+        assert_matches(|| Lagrange::<4>);
+        assert_matches(|| Lagrange::<5>);
+        assert_matches(|| Sinc::new(8, 64, 6.0));
+    }
+
+    #[test]
+    fn test_static_matches_heap() {
+        let mut allpass_filter_heap = AllPassFilter::new(100, 12.5, 0.5, Cubic);
+        let mut allpass_filter_static = StaticAllPassFilter::<_, _, 100>::new_static(12.5, 0.5, Cubic);
+
+        for i in 0..300 {
+            let input = if i % 70 == 0 { 1.0 } else { 0.0 };
+            let out_heap = allpass_filter_heap.process(input);
+            let out_static = allpass_filter_static.process(input);
+            assert!((out_heap - out_static).abs() < 1e-12, "Static storage should match heap storage");
+        }
+    }
+
+    #[test]
+    fn test_block_matches_single_with_modulated_delay() {
+        fn assert_matches<I: StatefulInterpolator<f64>>(make: impl Fn() -> I) {
+            let mut allpass_filter_single = AllPassFilter::new_mirrored(64, 1.5, 0.6, make());
+            let mut allpass_filter_block = AllPassFilter::new_mirrored(64, 1.5, 0.6, make());
+            allpass_filter_single.set_smoothing(0.05);
+            allpass_filter_block.set_smoothing(0.05);
+
+            let input: Vec<f64> = (0..1000).map(|i| if i % 97 == 0 { 1.0 } else { 0.0 }).collect();
+            let mut output_block = vec![0.0; input.len()];
+
+            // 遅延をブロック長より短い値と長い値の間で動かす
+            for (block, (input, output)) in input.chunks(150).zip(output_block.chunks_mut(150)).enumerate() {
+                let delay = if block % 2 == 0 { 50.25 } else { 2.5 };
+                allpass_filter_single.set_delay(delay);
+                allpass_filter_block.set_delay(delay);
+
+                allpass_filter_block.process_block(input, output);
+                for (&sample, &out_block) in input.iter().zip(output.iter()) {
+                    let out_single = allpass_filter_single.process(sample);
+                    assert!((out_single - out_block).abs() < 1e-12, "Block and single sample outputs should match");
+                }
+            }
+        }
+
+        assert_matches(|| Linear);
+        assert_matches(|| Cubic);
+        assert_matches(Thiran::new);
+        assert_matches(|| Sinc::new(8, 64, 6.0));
+    }
+
+    #[test]
+    fn test_sample_rate_change_rescales_delay() {
+        let mut allpass_filter = AllPassFilter::new_with_time(1000.0, DelayTime::Millis(50.0), DelayTime::Millis(10.0), 0.5, Linear);
+        assert_eq!(allpass_filter.max_delay(), 50);
+        assert_eq!(allpass_filter.delay_length.current(), 10.0);
+
+        allpass_filter.set_sample_rate(2000.0);
+        assert_eq!(allpass_filter.delay_time(), DelayTime::Millis(10.0));
+        assert_eq!(allpass_filter.delay_length.current(), 20.0);
+
+        // バッファに収まらない遅延は切り詰める
+        allpass_filter.set_sample_rate(8000.0);
+        assert_eq!(allpass_filter.delay_length.current(), 50.0);
+
+        // サンプル数で指定した遅延はサンプルレートに依存しない
+        allpass_filter.set_delay(5.0);
+        allpass_filter.set_sample_rate(1000.0);
+        assert_eq!(allpass_filter.delay_time(), DelayTime::Samples(5.0));
+    }
+
+    #[test]
+    fn test_reserved_sample_rate_change_keeps_history() {
+        let mut allpass_filter = AllPassFilter::new_with_time(1000.0, DelayTime::Millis(20.0), DelayTime::Millis(10.0), 0.0, Linear);
+        allpass_filter.reserve_with_time(DelayTime::Millis(20.0), 4000.0);
+        assert_eq!(allpass_filter.max_delay(), 20);
+
+        // インパルスが遅延線に入った直後にサンプルレートを上げる
+        allpass_filter.process(1.0);
+        allpass_filter.set_sample_rate(4000.0);
+        assert_eq!(allpass_filter.max_delay(), 80);
+
+        // 直近のサンプルは保持され、新しい遅延（40サンプル）で読まれる
+        let output: Vec<f64> = (0..45).map(|_| allpass_filter.process(0.0)).collect();
+        assert_eq!(output[39], 1.0);
+        assert_eq!(output.iter().filter(|&&y| y != 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_reset_matches_fresh_filter() {
+        let mut allpass_filter_fresh = AllPassFilter::new(100, 12.5, 0.5, Thiran::new());
+        let mut allpass_filter_reset = AllPassFilter::new(100, 30.0, 0.5, Thiran::new());
+
+        // 状態を汚してから目標値を揃えて初期化する
+        for i in 0..200 {
+            allpass_filter_reset.process(if i % 7 == 0 { 1.0 } else { -0.5 });
+        }
+        allpass_filter_reset.set_delay(12.5);
+        allpass_filter_reset.reset();
+
+        for i in 0..200 {
+            let input = if i % 50 == 0 { 1.0 } else { 0.0 };
+            let out_fresh = allpass_filter_fresh.process(input);
+            let out_reset = allpass_filter_reset.process(input);
+            assert_eq!(out_fresh, out_reset, "Reset filter should behave like a fresh one");
+        }
+    }
+
+    #[test]
+    fn test_restore_replays_output() {
+        fn assert_replays<I: StatefulInterpolator<f64>>(mut allpass_filter: AllPassFilter<f64, I>, before_save: impl Fn(&mut AllPassFilter<f64, I>)) {
+            for i in 0..100 {
+                allpass_filter.process(if i % 13 == 0 { 1.0 } else { 0.0 });
+            }
+            allpass_filter.set_delay(7.5);
+            before_save(&mut allpass_filter);
+
+            // 平滑化の途中で保存し、同じ入力で同じ出力が再現されることを確認
+            let snapshot = allpass_filter.snapshot();
+            let first: Vec<f64> = (0..100).map(|i| allpass_filter.process(i as f64 * 0.01)).collect();
+            allpass_filter.restore(&snapshot).unwrap();
+            let second: Vec<f64> = (0..100).map(|i| allpass_filter.process(i as f64 * 0.01)).collect();
+            assert_eq!(first, second);
+        }
+
+        assert_replays(AllPassFilter::new_mirrored(64, 20.0, 0.5, Cubic), |_| {});
+        // Thiran補間の直前の出力と、補間方法のクロスフェードの途中も再現する
+        assert_replays(AllPassFilter::new(64, 20.0, 0.5, Thiran::new()), |_| {});
+        assert_replays(AllPassFilter::new_dynamic(64, 20.0, 0.5, InterpolationMode::Thiran), |allpass_filter| {
+            allpass_filter.set_interpolation_mode(InterpolationMode::Cubic);
+            allpass_filter.process(0.0);
+        });
+        // LFOの位相と乱数の状態も再現する
+        for shape in [LfoShape::Sine, LfoShape::SmoothRandom] {
+            assert_replays(AllPassFilter::new(64, 20.0, 0.5, Cubic), |allpass_filter| {
+                allpass_filter.set_modulation(Some(Lfo::new(shape, 300.0, DelayTime::Samples(3.0), 44100.0)));
+                for _ in 0..250 {
+                    allpass_filter.process(0.0);
+                }
+            });
+        }
+
+        let mut allpass_filter = AllPassFilter::new(64, 20.0, 0.5, Cubic);
+        let snapshot = allpass_filter.snapshot();
+        let mut allpass_filter_small = AllPassFilter::new(32, 5.0, 0.5, Cubic);
+        assert_eq!(allpass_filter_small.restore(&snapshot), Err(Error::InvalidSnapshot));
+        let mut allpass_filter_thiran = AllPassFilter::new(64, 20.0, 0.5, Thiran::new());
+        assert_eq!(allpass_filter_thiran.restore(&snapshot), Err(Error::InvalidSnapshot));
+        let mut allpass_filter_modulated = AllPassFilter::new(64, 20.0, 0.5, Cubic);
+        allpass_filter_modulated.set_modulation(Some(Lfo::new(LfoShape::Sine, 1.0, DelayTime::Samples(1.0), 44100.0)));
+        assert_eq!(allpass_filter_modulated.restore(&snapshot), Err(Error::InvalidSnapshot));
+
+        // 書き換えられた状態の範囲外の遅延時間とゲインは拒否、またはガードで安定範囲に収める
+        let mut tampered = snapshot.clone();
+        tampered.delay_length.target = 100.0;
+        assert_eq!(allpass_filter.restore(&tampered), Err(Error::DelayOutOfRange));
+
+        let mut tampered = snapshot.clone();
+        tampered.g.current = 1.5;
+        tampered.g.target = 1.5;
+        allpass_filter.restore(&tampered).unwrap();
+        assert!(allpass_filter.g.current() < 1.0 && allpass_filter.g.target() < 1.0);
+
+        allpass_filter.set_gain_guard(GainGuard::new(GainPolicy::Reject, 0.9));
+        assert_eq!(allpass_filter.restore(&tampered), Err(Error::UnstableGain));
+    }
+
+    #[test]
+    fn test_smoothing_time_is_sample_rate_independent() {
+        // 時定数5 msの経過後、どちらのサンプルレートでも差が1/eになる
+        for sample_rate in [44100.0, 192000.0] {
+            let mut allpass_filter = AllPassFilter::new_with_time(sample_rate, DelayTime::Millis(20.0), DelayTime::Millis(0.0), 0.0, Linear);
+            allpass_filter.set_smoothing_time(5.0);
+            allpass_filter.set_gain(0.5).unwrap();
+
+            let samples = (sample_rate * 0.005) as usize;
+            for _ in 0..samples {
+                allpass_filter.process(0.0);
+            }
+            let remaining = (0.5 - allpass_filter.g.current()) / 0.5;
+            assert!((remaining - (-1.0f64).exp()).abs() < 1e-3, "Smoothing should depend on time, not samples");
+        }
+    }
+
+    #[test]
+    fn test_ramp_time_follows_sample_rate() {
+        // 5 msのランプはサンプルレートを変えても5 msで目標値に届く
+        let mut allpass_filter = AllPassFilter::new_with_time(44100.0, DelayTime::Millis(20.0), DelayTime::Millis(1.0), 0.0, Linear);
+        allpass_filter.set_smoothing_style(SmoothingStyle::Linear(DelayTime::Millis(5.0)));
+        allpass_filter.set_sample_rate(96000.0);
+        allpass_filter.set_gain(0.5).unwrap();
+
+        for _ in 0..479 {
+            allpass_filter.process(0.0);
+        }
+        assert!(allpass_filter.g.current() < 0.5);
+        allpass_filter.process(0.0);
+        assert_eq!(allpass_filter.g.current(), 0.5);
+    }
+
+    #[test]
+    fn test_static_block_matches_single() {
+        fn assert_matches<I: StatefulInterpolator<f64>>(make: impl Fn() -> I) {
+            assert_matches_at(&[1.5, 9.75, 40.25], make);
+        }
+
+        fn assert_matches_at<I: StatefulInterpolator<f64>>(delays: &[f64], make: impl Fn() -> I) {
+            for &initial_delay in delays {
+                let mut allpass_filter_single = AllPassFilter::new_pow2(64, initial_delay, 0.7, make());
+                let mut allpass_filter_block = AllPassFilter::new_pow2(64, initial_delay, 0.7, make());
+
+                let input: Vec<f64> = (0..500).map(|i| ((i * 37 % 101) as f64 - 50.0) / 50.0).collect();
+                let mut output_block = vec![0.0; input.len()];
+                allpass_filter_block.process_block(&input, &mut output_block);
+
+                for (&sample, &out_block) in input.iter().zip(output_block.iter()) {
+                    let out_single = allpass_filter_single.process(sample);
+                    assert!((out_single - out_block).abs() < 1e-12, "Static fast path should match single sample processing");
+                }
+            }
+        }
+
+        assert_matches(|| Linear);
+        assert_matches(|| Nearest);
+        assert_matches(|| Cubic);
+        assert_matches(|| Lagrange::<3>);
+        assert_matches(Thiran::new);
+
+        // カーネルの長い補間器は先読みが大きいため、遅延を`min_delay()`以上にする
+        assert_matches_at(&[5.5, 9.75, 40.25], || Lagrange::<7>);
+        assert_matches_at(&[5.5, 9.75, 40.25], || Sinc::new(8, 64, 6.0));
+    }
+
+    #[test]
+    fn test_params_from_another_thread() {
+        fn assert_send_sync<P: Send + Sync + Clone>() {}
+        assert_send_sync::<AllPassParams<f64>>();
+
+        let mut allpass_filter = AllPassFilter::new_default(100, 10.0, 0.5);
+        let params = AllPassParams::new();
+
+        // 未設定の値は変更しない
+        allpass_filter.apply_params(&params).unwrap();
+        assert_eq!(allpass_filter.delay_length.target(), 10.0);
+
+        let ui_params = params.clone();
+        std::thread::spawn(move || {
+            ui_params.set_delay(25.0);
+            ui_params.set_gain(0.25);
+            ui_params.set_smoothing(1.0);
+        })
+        .join()
+        .unwrap();
+
+        allpass_filter.apply_params(&params).unwrap();
+        allpass_filter.process(0.0);
+        assert_eq!(allpass_filter.delay_length.current(), 25.0);
+        assert_eq!(allpass_filter.g.current(), 0.25);
+
+        // ガードで拒否されたゲインはエラーになる
+        allpass_filter.set_gain_guard(GainGuard::new(GainPolicy::Reject, 0.9));
+        params.set_gain(1.5);
+        assert_eq!(allpass_filter.apply_params(&params), Err(Error::UnstableGain));
+
+        // 拒否されたゲインは次のブロックで再び適用しない
+        assert_eq!(allpass_filter.apply_params(&params), Ok(()));
+        assert_eq!(allpass_filter.g.target(), 0.25);
+
+        // 最大遅延を超える遅延時間は`max_delay()`に収める
+        params.set_delay(1000.0);
+        allpass_filter.apply_params(&params).unwrap();
+        assert_eq!(allpass_filter.delay_length.target(), allpass_filter.max_delay() as f64);
+    }
+
+    #[test]
+    fn test_events_apply_at_sample_offset() {
+        let mut allpass_filter_events = AllPassFilter::new(100, 10.0, 0.5, Cubic);
+        let mut allpass_filter_single = AllPassFilter::new(100, 10.0, 0.5, Cubic);
+        allpass_filter_events.set_smoothing(0.05);
+        allpass_filter_single.set_smoothing(0.05);
+
+        let events = [
+            ParamEvent::delay(37, DelayTime::Samples(20.5)),
+            ParamEvent::gain(37, 0.25),
+            ParamEvent::delay(150, DelayTime::Samples(6.25)),
+            ParamEvent::gain(199, 0.75),
+        ];
+
+        let input: Vec<f64> = (0..200).map(|i| if i % 30 == 0 { 1.0 } else { 0.0 }).collect();
+        let mut output = vec![0.0; 200];
+        allpass_filter_events.process_block_with_events(&input, &mut output, &events).unwrap();
+
+        for (i, (&x, &y)) in input.iter().zip(&output).enumerate() {
+            if i == 37 {
+                allpass_filter_single.set_delay(20.5);
+                allpass_filter_single.set_gain(0.25).unwrap();
+            }
+            if i == 150 {
+                allpass_filter_single.set_delay(6.25);
+            }
+            if i == 199 {
+                allpass_filter_single.set_gain(0.75).unwrap();
+            }
+            assert!((allpass_filter_single.process(x) - y).abs() < 1e-12, "Event should take effect at sample {i}");
+        }
+        assert_eq!(allpass_filter_events.g.target(), 0.75);
+
+        // 拒否されたゲインはエラーを返すが、後続のイベントは反映される
+        allpass_filter_events.set_gain_guard(GainGuard::new(GainPolicy::Reject, 0.9));
+        let events = [ParamEvent::gain(0, 1.5), ParamEvent::delay(10, DelayTime::Samples(12.0))];
+        let result = allpass_filter_events.process_block_with_events(&input[..20], &mut output[..20], &events);
+        assert_eq!(result, Err(Error::UnstableGain));
+        assert_eq!(allpass_filter_events.delay_length.target(), 12.0);
+
+        // ブロック外や昇順でないイベントは拒否し、どれも反映しない
+        for events in [
+            [ParamEvent::delay(5, DelayTime::Samples(30.0)), ParamEvent::delay(20, DelayTime::Samples(40.0))],
+            [ParamEvent::delay(15, DelayTime::Samples(30.0)), ParamEvent::delay(5, DelayTime::Samples(40.0))],
+        ] {
+            let result = allpass_filter_events.process_block_with_events(&input[..20], &mut output[..20], &events);
+            assert_eq!(result, Err(Error::InvalidEvent));
+            assert_eq!(allpass_filter_events.delay_length.target(), 12.0);
+        }
+    }
+
+    #[test]
+    fn test_lfo_modulates_delay_in_block_and_single() {
+        for shape in [LfoShape::Sine, LfoShape::Triangle, LfoShape::SmoothRandom] {
+            let lfo = Lfo::new(shape, 200.0, DelayTime::Samples(3.0), 44100.0);
+            let mut allpass_filter_block = AllPassFilter::new(100, 20.0, 0.5, Cubic);
+            let mut allpass_filter_single = AllPassFilter::new(100, 20.0, 0.5, Cubic);
+            let mut allpass_filter_plain = AllPassFilter::new(100, 20.0, 0.5, Cubic);
+            allpass_filter_block.set_modulation(Some(lfo));
+            allpass_filter_single.set_modulation(Some(lfo));
+
+            let input: Vec<f64> = (0..500).map(|i| if i % 50 == 0 { 1.0 } else { 0.0 }).collect();
+            let mut output = vec![0.0; 500];
+            allpass_filter_block.process_block(&input, &mut output);
+
+            let mut differs = false;
+            for (&x, &y) in input.iter().zip(&output) {
+                assert!((allpass_filter_single.process(x) - y).abs() < 1e-12, "{shape:?} block should match single");
+                differs |= (allpass_filter_plain.process(x) - y).abs() > 1e-6;
+            }
+            assert!(differs, "{shape:?} should modulate the delay");
+        }
+    }
+}
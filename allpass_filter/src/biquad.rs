@@ -0,0 +1,179 @@
+﻿use num_traits::Float;
+use crate::parameter::{SmoothedParam, DEFAULT_SMOOTHING_TIME_MS};
+use crate::processor::Processor;
+
+/// 2次（双二次）オールパスフィルタ
+/// 中心周波数で位相が180度回転し、Qが高いほど回転が急峻になる
+/// 式: H(z) = (a2 + a1 z^-1 + z^-2) / (1 + a1 z^-1 + a2 z^-2)
+pub struct BiquadAllPass<T> {
+    frequency: SmoothedParam<T>, // 中心周波数（Hz）
+    q: SmoothedParam<T>,         // Q値
+    sample_rate: T,              // サンプルレート（Hz）
+    coeff_frequency: T,          // 係数計算に使用した中心周波数
+    coeff_q: T,                  // 係数計算に使用したQ値
+    a1: T,                       // 係数 a1 (= b1)
+    a2: T,                       // 係数 a2 (= b0)
+    s1: T,                       // 状態変数1（転置直接形II）
+    s2: T,                       // 状態変数2（転置直接形II）
+}
+
+impl<T: Float> BiquadAllPass<T> {
+    /// 新しいBiquadAllPassを作成
+    /// `sample_rate`: サンプルレート（Hz）
+    /// `frequency`: 中心周波数（Hz）
+    /// `q`: Q値
+    pub fn new(sample_rate: T, frequency: T, q: T) -> Self {
+        let smoothing_time = T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap();
+
+        let mut filter = Self {
+            frequency: SmoothedParam::with_time_constant(frequency, smoothing_time, sample_rate),
+            q: SmoothedParam::with_time_constant(q, smoothing_time, sample_rate),
+            sample_rate,
+            coeff_frequency: frequency,
+            coeff_q: q,
+            a1: T::zero(),
+            a2: T::zero(),
+            s1: T::zero(),
+            s2: T::zero(),
+        };
+        filter.update_coefficients(frequency, q);
+        filter
+    }
+
+    /// 平滑化係数を設定
+    /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
+    pub fn set_smoothing(&mut self, factor: T) {
+        self.frequency.set_factor(factor);
+        self.q.set_factor(factor);
+    }
+
+    /// 係数を再計算
+    /// `frequency`: 中心周波数（Hz）
+    /// `q`: Q値
+    fn update_coefficients(&mut self, frequency: T, q: T) {
+        let two = T::from(2.0).unwrap();
+        let omega = two * T::from(core::f64::consts::PI).unwrap() * frequency / self.sample_rate;
+        let alpha = omega.sin() / (two * q);
+        let a0 = T::one() + alpha;
+
+        self.a1 = (-two * omega.cos()) / a0;
+        self.a2 = (T::one() - alpha) / a0;
+        self.coeff_frequency = frequency;
+        self.coeff_q = q;
+    }
+
+    /// オーディオサンプルを処理
+    /// `input`: 入力サンプル
+    /// 戻り値: 出力サンプル
+    #[inline]
+    pub fn process(&mut self, input: T) -> T {
+        let current_frequency = self.frequency.next();
+        let current_q = self.q.next();
+
+        // パラメータが動いている間だけ係数を再計算
+        if current_frequency != self.coeff_frequency || current_q != self.coeff_q {
+            self.update_coefficients(current_frequency, current_q);
+        }
+
+        // 転置直接形II (b0 = a2, b1 = a1, b2 = 1)
+        let output = self.a2 * input + self.s1;
+        self.s1 = self.a1 * input - self.a1 * output + self.s2;
+        self.s2 = input - self.a2 * output;
+
+        output
+    }
+
+    /// ブロック単位でオーディオサンプルを処理
+    /// `input`: 入力サンプルのスライス
+    /// `output`: 出力サンプルのスライス
+    pub fn process_block(&mut self, input: &[T], output: &mut [T]) {
+        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+            *out_sample = self.process(*in_sample);
+        }
+    }
+
+    /// ブロック単位でオーディオサンプルをインプレース処理
+    /// `buffer`: 入出力サンプルのスライス
+    pub fn process_block_inplace(&mut self, buffer: &mut [T]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+
+    /// 状態変数をゼロにし、パラメータを目標値に合わせる
+    pub fn reset(&mut self) {
+        self.frequency.reset();
+        self.q.reset();
+        self.s1 = T::zero();
+        self.s2 = T::zero();
+    }
+
+    /// 中心周波数を設定
+    /// `frequency`: 中心周波数（Hz）
+    pub fn set_frequency(&mut self, frequency: T) {
+        self.frequency.set_target(frequency);
+    }
+
+    /// Q値を設定
+    /// `q`: Q値
+    pub fn set_q(&mut self, q: T) {
+        self.q.set_target(q);
+    }
+}
+
+impl<T: Float> Processor<T> for BiquadAllPass<T> {
+    #[inline]
+    fn process(&mut self, input: T) -> T {
+        BiquadAllPass::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        BiquadAllPass::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// インパルス応答から周波数`frequency`における振幅を求める
+    fn magnitude_at(impulse_response: &[f64], frequency: f64, sample_rate: f64) -> f64 {
+        let omega = 2.0 * core::f64::consts::PI * frequency / sample_rate;
+        let (mut re, mut im) = (0.0, 0.0);
+        for (n, h) in impulse_response.iter().enumerate() {
+            re += h * (omega * n as f64).cos();
+            im -= h * (omega * n as f64).sin();
+        }
+        (re * re + im * im).sqrt()
+    }
+
+    #[test]
+    fn test_unity_magnitude() {
+        let sample_rate = 48000.0;
+        let mut biquad = BiquadAllPass::new(sample_rate, 1000.0, 0.707);
+
+        let mut impulse_response = vec![0.0; 8192];
+        impulse_response[0] = 1.0;
+        biquad.process_block_inplace(&mut impulse_response);
+
+        for &frequency in &[50.0, 500.0, 1000.0, 4000.0, 12000.0, 20000.0] {
+            let magnitude = magnitude_at(&impulse_response, frequency, sample_rate);
+            assert!((magnitude - 1.0).abs() < 1e-6, "Magnitude at {} Hz should be 1.0, got {}", frequency, magnitude);
+        }
+    }
+
+    #[test]
+    fn test_unity_magnitude_high_q() {
+        let sample_rate = 44100.0;
+        let mut biquad = BiquadAllPass::new(sample_rate, 3000.0, 8.0);
+
+        let mut impulse_response = vec![0.0; 16384];
+        impulse_response[0] = 1.0;
+        biquad.process_block_inplace(&mut impulse_response);
+
+        for &frequency in &[100.0, 2900.0, 3000.0, 3100.0, 10000.0] {
+            let magnitude = magnitude_at(&impulse_response, frequency, sample_rate);
+            assert!((magnitude - 1.0).abs() < 1e-6, "Magnitude at {} Hz should be 1.0, got {}", frequency, magnitude);
+        }
+    }
+}
@@ -1,8 +1,34 @@
-﻿use crate::{AllPassFilter, Linear};
+﻿use crate::{AllPassFilter, AllPassFilterSnapshot, AllPassParams, DelayLineSnapshot, DynamicInterpolator, GainGuard, GainPolicy, InterpolationMode, InterpolatorState, SmoothedParamSnapshot};
 use alloc::boxed::Box;
+use alloc::vec::Vec;
 use core::slice;
 
-pub type CAllPass = AllPassFilter<f32, Linear>;
+pub type CAllPass = AllPassFilter<f32, DynamicInterpolator<f32>>;
+pub type CAllPassParams = AllPassParams<f32>;
+
+/// C API用の補間方法の番号を`InterpolationMode`に変換
+/// 0: Nearest, 1: Linear, 2: Cubic, 3: Sinc, 4: Thiran
+fn interpolation_mode_from_index(mode: u32) -> Option<InterpolationMode> {
+    match mode {
+        0 => Some(InterpolationMode::Nearest),
+        1 => Some(InterpolationMode::Linear),
+        2 => Some(InterpolationMode::Cubic),
+        3 => Some(InterpolationMode::Sinc),
+        4 => Some(InterpolationMode::Thiran),
+        _ => None,
+    }
+}
+
+/// `InterpolationMode`をC API用の補間方法の番号に変換
+fn interpolation_mode_index(mode: InterpolationMode) -> u32 {
+    match mode {
+        InterpolationMode::Nearest => 0,
+        InterpolationMode::Linear => 1,
+        InterpolationMode::Cubic => 2,
+        InterpolationMode::Sinc => 3,
+        InterpolationMode::Thiran => 4,
+    }
+}
 
 /// AllPassFilterインスタンスの生成
 /// `max_delay`: 最大遅延長（サンプル単位）
@@ -11,7 +37,28 @@ pub type CAllPass = AllPassFilter<f32, Linear>;
 /// 戻り値: 生成されたAllPassFilterインスタンスへのポインタ
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn allpass_create(max_delay: usize, initial_delay: f32, gain: f32) -> *mut CAllPass {
-    let apf = AllPassFilter::new_default(max_delay, initial_delay, gain);
+    let apf = AllPassFilter::new_dynamic(max_delay, initial_delay, gain, InterpolationMode::Linear);
+    Box::into_raw(Box::new(apf))
+}
+
+/// 補間方法を指定してAllPassFilterインスタンスを生成
+/// `max_delay`: 最大遅延長（サンプル単位）
+/// `initial_delay`: 初期遅延時間（サンプル単位）
+/// `gain`: フィードバックゲイン
+/// `mode`: 補間方法 (0: Nearest, 1: Linear, 2: Cubic, 3: Sinc, 4: Thiran)
+/// 戻り値: 生成されたAllPassFilterインスタンスへのポインタ（不正な補間方法の場合はnull）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_create_with_interpolation(
+    max_delay: usize,
+    initial_delay: f32,
+    gain: f32,
+    mode: u32,
+) -> *mut CAllPass {
+    let mode = match interpolation_mode_from_index(mode) {
+        Some(mode) => mode,
+        None => return core::ptr::null_mut(),
+    };
+    let apf = AllPassFilter::new_dynamic(max_delay, initial_delay, gain, mode);
     Box::into_raw(Box::new(apf))
 }
 
@@ -58,10 +105,33 @@ pub unsafe extern "C" fn allpass_set_delay(ptr: *mut CAllPass, delay: f32) {
 }
 
 /// ゲインの設定
+/// 戻り値: 成功した場合はtrue、Rejectポリシーで範囲外の場合はfalse
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_set_gain(ptr: *mut CAllPass, gain: f32) -> bool {
+    let apf = &mut *ptr;
+    apf.set_gain(gain).is_ok()
+}
+
+/// ゲインの安定化ポリシーの設定
+/// `policy`: 範囲外の値の扱い (0: Clamp, 1: Reject, 2: SoftLimit)
+/// `max_gain`: |g|の上限 (0 < max_gain < 1)
+/// 戻り値: 成功した場合はtrue、不正な引数の場合はfalse
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn allpass_set_gain(ptr: *mut CAllPass, gain: f32) {
+pub unsafe extern "C" fn allpass_set_gain_policy(ptr: *mut CAllPass, policy: u32, max_gain: f32) -> bool {
     let apf = &mut *ptr;
-    apf.set_gain(gain);
+    let policy = match policy {
+        0 => GainPolicy::Clamp,
+        1 => GainPolicy::Reject,
+        2 => GainPolicy::SoftLimit,
+        _ => return false,
+    };
+    match GainGuard::try_new(policy, max_gain) {
+        Ok(gain_guard) => {
+            apf.set_gain_guard(gain_guard);
+            true
+        }
+        Err(_) => false,
+    }
 }
 
 /// 平滑化係数の設定
@@ -70,3 +140,195 @@ pub unsafe extern "C" fn allpass_set_smoothing(ptr: *mut CAllPass, factor: f32)
     let apf = &mut *ptr;
     apf.set_smoothing(factor);
 }
+
+/// 内部状態の初期化（遅延線をゼロで埋め、パラメータを目標値に合わせる）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_reset(ptr: *mut CAllPass) {
+    let apf = &mut *ptr;
+    apf.reset();
+}
+
+/// 補間方法の設定（クロスフェード付きで切り替え）
+/// `mode`: 補間方法 (0: Nearest, 1: Linear, 2: Cubic, 3: Sinc, 4: Thiran)
+/// 戻り値: 成功した場合はtrue、不正な補間方法の場合はfalse
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_set_interpolation(ptr: *mut CAllPass, mode: u32) -> bool {
+    let apf = &mut *ptr;
+    match interpolation_mode_from_index(mode) {
+        Some(mode) => {
+            apf.set_interpolation_mode(mode);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 保存データの先頭に置く識別子（形式を変えたら末尾の番号を上げる）
+const STATE_MAGIC: [u8; 4] = *b"APF2";
+
+/// 補間器の状態を含まない旧形式の識別子（補間器の状態を復元できないため読み込まない）
+const LEGACY_STATE_MAGIC: [u8; 4] = *b"APF1";
+
+/// 平滑化状態1つ分の長さ（現在値、目標値、ランプの始点、ランプの進行位置）
+const PARAM_STATE_LEN: usize = 4 * 4;
+
+/// 補間器の状態の長さ（補間方法、クロスフェード元の補間方法、クロスフェードの進行位置、Thiran補間の直前の出力）
+const INTERPOLATOR_STATE_LEN: usize = 4 * 4;
+
+/// 保存データのヘッダ長（識別子、バッファ長、書き込み位置、平滑化状態2つ、補間器の状態）
+const STATE_HEADER_LEN: usize = 4 + 4 + 4 + PARAM_STATE_LEN * 2 + INTERPOLATOR_STATE_LEN;
+
+/// 平滑化状態をリトルエンディアンで追加
+fn encode_param(bytes: &mut Vec<u8>, param: &SmoothedParamSnapshot<f32>) {
+    bytes.extend_from_slice(&param.current.to_le_bytes());
+    bytes.extend_from_slice(&param.target.to_le_bytes());
+    bytes.extend_from_slice(&param.start.to_le_bytes());
+    bytes.extend_from_slice(&(param.ramp_pos as u32).to_le_bytes());
+}
+
+/// スナップショットをリトルエンディアンのバイト列に変換
+fn encode_state(snapshot: &AllPassFilterSnapshot<f32>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(STATE_HEADER_LEN + snapshot.delay_line.buffer.len() * 4);
+    bytes.extend_from_slice(&STATE_MAGIC);
+    bytes.extend_from_slice(&(snapshot.delay_line.buffer.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(snapshot.delay_line.writer_ptr as u32).to_le_bytes());
+    encode_param(&mut bytes, &snapshot.delay_length);
+    encode_param(&mut bytes, &snapshot.g);
+
+    // CAllPassの補間器は常にDynamicInterpolator
+    let InterpolatorState::Dynamic { mode, prev_mode, fade_pos, prev_output } = snapshot.delay_line.interpolator else {
+        unreachable!("CAllPass always uses DynamicInterpolator");
+    };
+    bytes.extend_from_slice(&interpolation_mode_index(mode).to_le_bytes());
+    bytes.extend_from_slice(&interpolation_mode_index(prev_mode).to_le_bytes());
+    bytes.extend_from_slice(&(fade_pos as u32).to_le_bytes());
+    bytes.extend_from_slice(&prev_output.to_le_bytes());
+
+    for sample in &snapshot.delay_line.buffer {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+/// `encode_state`で作ったバイト列をスナップショットに戻す
+fn decode_state(bytes: &[u8]) -> Option<AllPassFilterSnapshot<f32>> {
+    let word = |index: usize| -> [u8; 4] { bytes[index * 4..index * 4 + 4].try_into().unwrap() };
+    let param = |index: usize| SmoothedParamSnapshot {
+        current: f32::from_le_bytes(word(index)),
+        target: f32::from_le_bytes(word(index + 1)),
+        start: f32::from_le_bytes(word(index + 2)),
+        ramp_pos: u32::from_le_bytes(word(index + 3)) as usize,
+    };
+
+    if bytes.len() >= 4 && word(0) == LEGACY_STATE_MAGIC {
+        return None;
+    }
+    if bytes.len() < STATE_HEADER_LEN || word(0) != STATE_MAGIC {
+        return None;
+    }
+    let len = u32::from_le_bytes(word(1)) as usize;
+    let expected_len = len.checked_mul(4).and_then(|n| n.checked_add(STATE_HEADER_LEN))?;
+    if bytes.len() != expected_len {
+        return None;
+    }
+
+    let interpolator_index = 3 + PARAM_STATE_LEN / 4 * 2;
+    let interpolator = InterpolatorState::Dynamic {
+        mode: interpolation_mode_from_index(u32::from_le_bytes(word(interpolator_index)))?,
+        prev_mode: interpolation_mode_from_index(u32::from_le_bytes(word(interpolator_index + 1)))?,
+        fade_pos: u32::from_le_bytes(word(interpolator_index + 2)) as usize,
+        prev_output: f32::from_le_bytes(word(interpolator_index + 3)),
+    };
+
+    Some(AllPassFilterSnapshot {
+        delay_line: DelayLineSnapshot {
+            buffer: (0..len).map(|i| f32::from_le_bytes(word(STATE_HEADER_LEN / 4 + i))).collect(),
+            writer_ptr: u32::from_le_bytes(word(2)) as usize,
+            interpolator,
+        },
+        delay_length: param(3),
+        g: param(3 + PARAM_STATE_LEN / 4),
+        // C APIはLFOを公開していないため、CAllPassは常に変調なし
+        lfo: None,
+    })
+}
+
+/// 内部状態（遅延線の内容、書き込み位置、平滑化状態、補間器の状態）をバイト列に保存
+/// `out`: 書き込み先（nullの場合は必要なサイズだけを返す）
+/// `capacity`: 書き込み先のサイズ（バイト）
+/// 戻り値: 保存に必要なサイズ（バイト）。`capacity`が足りない場合は何も書き込まない
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_save_state(ptr: *const CAllPass, out: *mut u8, capacity: usize) -> usize {
+    let apf = &*ptr;
+    let bytes = encode_state(&apf.snapshot());
+    if !out.is_null() && capacity >= bytes.len() {
+        let out = slice::from_raw_parts_mut(out, bytes.len());
+        out.copy_from_slice(&bytes);
+    }
+    bytes.len()
+}
+
+/// `allpass_save_state`で保存した内部状態を復元
+/// `data`: 保存したバイト列
+/// `len`: バイト列の長さ
+/// 戻り値: 成功した場合はtrue、データが壊れているかバッファに収まらない場合はfalse
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_load_state(ptr: *mut CAllPass, data: *const u8, len: usize) -> bool {
+    let apf = &mut *ptr;
+    if data.is_null() {
+        return false;
+    }
+    let bytes = slice::from_raw_parts(data, len);
+    match decode_state(bytes) {
+        Some(snapshot) => apf.restore(&snapshot).is_ok(),
+        None => false,
+    }
+}
+
+/// スレッド間で共有するパラメータの生成
+/// UIスレッドとオーディオスレッドで同じポインタをロックなしで使える
+/// 戻り値: 生成されたAllPassParamsへのポインタ
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_params_create() -> *mut CAllPassParams {
+    Box::into_raw(Box::new(AllPassParams::new()))
+}
+
+/// 共有パラメータの破棄（すべてのスレッドが使い終えてから呼ぶ）
+/// `params`: 破棄するAllPassParamsのポインタ
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_params_destroy(params: *mut CAllPassParams) {
+    if params.is_null() {
+        return;
+    }
+    let _ = Box::from_raw(params);
+}
+
+/// 遅延時間の目標値を書き込む（どのスレッドからでも呼べる）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_params_set_delay(params: *const CAllPassParams, delay: f32) {
+    let params = &*params;
+    params.set_delay(delay);
+}
+
+/// フィードバックゲインの目標値を書き込む（どのスレッドからでも呼べる）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_params_set_gain(params: *const CAllPassParams, gain: f32) {
+    let params = &*params;
+    params.set_gain(gain);
+}
+
+/// 平滑化係数を書き込む（どのスレッドからでも呼べる）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_params_set_smoothing(params: *const CAllPassParams, factor: f32) {
+    let params = &*params;
+    params.set_smoothing(factor);
+}
+
+/// 共有パラメータの最新値をフィルタに読み込む（オーディオスレッドでブロックごとに呼ぶ）
+/// 戻り値: 成功した場合はtrue、ゲインがガードで拒否された場合はfalse
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn allpass_apply_params(ptr: *mut CAllPass, params: *const CAllPassParams) -> bool {
+    let apf = &mut *ptr;
+    let params = &*params;
+    apf.apply_params(params).is_ok()
+}
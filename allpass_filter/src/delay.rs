@@ -1,17 +1,96 @@
 ﻿use num_traits::Float;
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
 use alloc::vec;
-use crate::interpolation::{Interpolator, Linear};
+use crate::error::Error;
+use crate::interpolation::{Interpolator, StatefulInterpolator};
+#[cfg(feature = "alloc")]
+use crate::interpolation::InterpolatorState;
+use crate::time::DelayTime;
 
-pub struct DelayLine<T, I> {
-    buffer: Vec<T>,    // 遅延バッファ
-    writer_ptr: usize, // 書き込みポインタ
-    interpolator: I,   // 補間方法
+/// 遅延バッファの格納方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Storage {
+    /// 剰余で折り返す通常のリングバッファ
+    Modulo,
+    /// 長さが2の累乗で、ビットマスクで折り返すリングバッファ
+    PowerOfTwo(usize),
+    /// 各サンプルを2回書き込む2倍長バッファ
+    Mirrored,
 }
 
-impl<T: Float, I: Interpolator<T>> DelayLine<T, I> {
+/// 遅延線のサンプルを保持する領域のトレイト
+/// ヒープ上の`Vec<T>`と固定長配列`[T; N]`を同じ`DelayLine`で扱うために使う
+pub trait DelayBuffer<T> {
+    fn as_slice(&self) -> &[T];
+    fn as_mut_slice(&mut self) -> &mut [T];
+}
+
+#[cfg(feature = "alloc")]
+impl<T> DelayBuffer<T> for Vec<T> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> DelayBuffer<T> for [T; N] {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+/// `DelayLine`のバッファ内容と書き込み位置
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg(feature = "alloc")]
+pub struct DelayLineSnapshot<T> {
+    pub buffer: Vec<T>,                     // 使用中のリングバッファの内容
+    pub writer_ptr: usize,                  // 書き込みポインタ
+    pub interpolator: InterpolatorState<T>, // 補間器の内部状態
+}
+
+#[cfg(feature = "alloc")]
+pub struct DelayLine<T, I, S = Vec<T>> {
+    buffer: S,               // 遅延バッファ
+    writer_ptr: usize,       // 書き込みポインタ
+    interpolator: I,         // 補間方法
+    storage: Storage,        // 格納方式
+    len: usize,              // 使用中のリングバッファ長
+    _sample: PhantomData<T>, // サンプルの型
+}
+
+/// allocが無効な場合は遅延バッファの型を省略できない（`StaticDelayLine`を使う）
+#[cfg(not(feature = "alloc"))]
+pub struct DelayLine<T, I, S> {
+    buffer: S,               // 遅延バッファ
+    writer_ptr: usize,       // 書き込みポインタ
+    interpolator: I,         // 補間方法
+    storage: Storage,        // 格納方式
+    len: usize,              // 使用中のリングバッファ長
+    _sample: PhantomData<T>, // サンプルの型
+}
+
+/// 固定長配列を遅延バッファに使うDelayLine（アロケータ不要）
+pub type StaticDelayLine<T, I, const N: usize> = DelayLine<T, I, [T; N]>;
+
+#[cfg(feature = "alloc")]
+impl<T: Float, I> DelayLine<T, I> {
     /// 新しいDelayLineを作成
-    /// `max_delay`: 最大遅延サンプル数
+    /// `max_delay`: 最大遅延サンプル数（0の場合は`push`でパニックする。検証には`try_new`を使う）
     /// `interpolator`: 補間方法
     pub fn new(max_delay: usize, interpolator: I) -> Self {
         let buffer = vec![T::zero(); max_delay];
@@ -19,22 +98,313 @@ impl<T: Float, I: Interpolator<T>> DelayLine<T, I> {
             buffer,
             writer_ptr: 0,
             interpolator,
+            storage: Storage::Modulo,
+            len: max_delay,
+            _sample: PhantomData,
+        }
+    }
+
+    /// バッファ長を2の累乗に切り上げたDelayLineを作成
+    /// インデックスの折り返しを剰余の代わりにビットマスクで行う
+    /// `max_delay`: 最大遅延サンプル数（2の累乗に切り上げられる）
+    /// `interpolator`: 補間方法
+    pub fn new_pow2(max_delay: usize, interpolator: I) -> Self {
+        let len = max_delay.max(1).next_power_of_two();
+        Self {
+            buffer: vec![T::zero(); len],
+            writer_ptr: 0,
+            interpolator,
+            storage: Storage::PowerOfTwo(len - 1),
+            len,
+            _sample: PhantomData,
+        }
+    }
+
+    /// 各サンプルを2回書き込むミラーバッファのDelayLineを作成
+    /// 補間カーネルを折り返しなしの連続したスライスとして読み出せる
+    /// `max_delay`: 最大遅延サンプル数（メモリは2倍確保される）
+    /// `interpolator`: 補間方法
+    pub fn new_mirrored(max_delay: usize, interpolator: I) -> Self {
+        Self {
+            buffer: vec![T::zero(); max_delay * 2],
+            writer_ptr: 0,
+            interpolator,
+            storage: Storage::Mirrored,
+            len: max_delay,
+            _sample: PhantomData,
+        }
+    }
+
+    /// 時間単位の最大遅延からDelayLineを作成
+    /// 補間器のヘッドルームを含め、`max_delay()`が指定の遅延以上になるバッファ長を確保する
+    /// `max_delay`: 最大遅延時間
+    /// `sample_rate`: サンプルレート（Hz）
+    /// `interpolator`: 補間方法
+    pub fn new_with_time(max_delay: DelayTime<T>, sample_rate: T, interpolator: I) -> Self
+    where
+        I: StatefulInterpolator<T>,
+    {
+        Self::new(len_for(max_delay, sample_rate, interpolator.headroom()), interpolator)
+    }
+
+    /// 最大遅延サンプル数を収められるようにバッファを確保する（アロケーションを伴う）
+    /// 使用中の長さと内容は変わらず、以降の`resize`をアロケーションなしで行える
+    /// `max_delay`: 確保する最大遅延サンプル数（2の累乗バッファでは2の累乗に切り上げる）
+    pub fn reserve(&mut self, max_delay: usize) {
+        let capacity = match self.storage {
+            Storage::PowerOfTwo(_) => max_delay.next_power_of_two(),
+            _ => max_delay,
+        };
+        if capacity <= self.capacity() {
+            return;
+        }
+
+        match self.storage {
+            Storage::Mirrored => self.buffer.resize(capacity * 2, T::zero()),
+            _ => self.buffer.resize(capacity, T::zero()),
+        }
+    }
+
+    /// 最大サンプルレートでの最大遅延時間を収められるようにバッファを確保する（アロケーションを伴う）
+    /// `max_delay`: 最大遅延時間
+    /// `max_sample_rate`: 想定する最大のサンプルレート（Hz）
+    pub fn reserve_with_time(&mut self, max_delay: DelayTime<T>, max_sample_rate: T)
+    where
+        I: StatefulInterpolator<T>,
+    {
+        self.reserve(len_for(max_delay, max_sample_rate, self.interpolator.headroom()));
+    }
+
+    /// バッファサイズを検証してDelayLineを作成
+    /// `max_delay`: 最大遅延サンプル数（バッファ長）
+    /// `interpolator`: 補間方法
+    /// 戻り値: 生成したDelayLine（補間器のヘッドルームを含めてバッファが足りない場合はエラー）
+    pub fn try_new(max_delay: usize, interpolator: I) -> Result<Self, Error>
+    where
+        I: StatefulInterpolator<T>,
+    {
+        if max_delay <= interpolator.headroom() {
+            return Err(Error::InvalidBufferSize);
+        }
+
+        Ok(Self::new(max_delay, interpolator))
+    }
+}
+
+impl<T: Float, I, const N: usize> DelayLine<T, I, [T; N]> {
+    /// 固定長配列を遅延バッファに使うDelayLineを作成
+    /// Nが2の累乗の場合は折り返しにビットマスクを使う
+    /// `interpolator`: 補間方法
+    pub fn new_static(interpolator: I) -> Self {
+        const { assert!(N > 0, "StaticDelayLine capacity must be greater than 0") };
+
+        let storage = if N.is_power_of_two() {
+            Storage::PowerOfTwo(N - 1)
+        } else {
+            Storage::Modulo
+        };
+
+        Self {
+            buffer: [T::zero(); N],
+            writer_ptr: 0,
+            interpolator,
+            storage,
+            len: N,
+            _sample: PhantomData,
+        }
+    }
+}
+
+impl<T: Float, I, S: DelayBuffer<T>> DelayLine<T, I, S> {
+    /// リングバッファとしての長さ
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 補間器に渡すバッファの終端（ミラーバッファは使用中の長さの2倍）
+    #[inline]
+    fn active_end(&self) -> usize {
+        match self.storage {
+            Storage::Mirrored => self.len * 2,
+            _ => self.len,
+        }
+    }
+
+    /// 確保済みのリングバッファ長（`resize`で指定できる最大の長さ）
+    pub fn capacity(&self) -> usize {
+        let len = self.buffer.as_slice().len();
+        match self.storage {
+            Storage::Mirrored => len / 2,
+            _ => len,
         }
     }
 
+    /// 確保済みの領域内でリングバッファ長を変更する（アロケーションを行わない）
+    /// 直近のサンプルは新しい長さに収まる分だけ保持し、残りはゼロで埋める
+    /// `len`: 新しいリングバッファ長（2の累乗バッファでは2の累乗に切り上げる）
+    /// 戻り値: 0または確保済みの長さを超える場合はエラー
+    pub fn resize(&mut self, len: usize) -> Result<(), Error> {
+        let len = match self.storage {
+            Storage::PowerOfTwo(_) => len.next_power_of_two(),
+            _ => len,
+        };
+        if len == 0 || len > self.capacity() {
+            return Err(Error::InvalidBufferSize);
+        }
+
+        let old_len = self.len;
+        let buffer = self.buffer.as_mut_slice();
+
+        // 古い順に並べ替え、新しい長さの末尾に直近のサンプルが来るように移す
+        buffer[..old_len].rotate_left(self.writer_ptr);
+        if len >= old_len {
+            buffer.copy_within(..old_len, len - old_len);
+            buffer[..len - old_len].fill(T::zero());
+        } else {
+            buffer.copy_within(old_len - len..old_len, 0);
+        }
+
+        match self.storage {
+            Storage::PowerOfTwo(_) => self.storage = Storage::PowerOfTwo(len - 1),
+            Storage::Mirrored => buffer.copy_within(..len, len),
+            Storage::Modulo => {}
+        }
+        self.len = len;
+        self.writer_ptr = 0;
+
+        Ok(())
+    }
+
+    /// 時間単位の最大遅延に合わせてリングバッファ長を変更する（アロケーションを行わない）
+    /// `max_delay`: 最大遅延時間
+    /// `sample_rate`: サンプルレート（Hz）
+    /// 戻り値: 確保済みの長さを超える場合はエラー
+    pub fn resize_with_time(&mut self, max_delay: DelayTime<T>, sample_rate: T) -> Result<(), Error>
+    where
+        I: StatefulInterpolator<T>,
+    {
+        self.resize(len_for(max_delay, sample_rate, self.interpolator.headroom()))
+    }
+
     /// 入力サンプルを遅延線にプッシュ
     /// `input`: 入力サンプル
     pub fn push(&mut self, input: T) {
-        self.buffer[self.writer_ptr] = input;
-        self.writer_ptr = (self.writer_ptr + 1) % self.buffer.len();
+        let len = self.len();
+        let buffer = self.buffer.as_mut_slice();
+
+        buffer[self.writer_ptr] = input;
+        self.writer_ptr = match self.storage {
+            Storage::Modulo => (self.writer_ptr + 1) % len,
+            Storage::PowerOfTwo(mask) => (self.writer_ptr + 1) & mask,
+            Storage::Mirrored => {
+                buffer[self.writer_ptr + len] = input;
+                (self.writer_ptr + 1) % len
+            }
+        };
     }
 
-    /// 補間付きで遅延線からサンプルを読み出す
+    /// 入力ブロックをまとめて遅延線に書き込む
+    /// `push`を1サンプルずつ呼ぶのと同じ結果になる
+    /// `input`: 入力サンプル列
+    pub fn write_block(&mut self, input: &[T]) {
+        if input.is_empty() {
+            return;
+        }
+        let len = self.len();
+
+        // バッファより長い入力は末尾のlenサンプルだけが残る
+        let skip = input.len().saturating_sub(len);
+        let input = &input[skip..];
+        let start = (self.writer_ptr + skip) % len;
+
+        // 書き込み先を折り返し前後の2区間に分けてコピーする
+        let first = input.len().min(len - start);
+        let (head, tail) = input.split_at(first);
+        let buffer = self.buffer.as_mut_slice();
+        buffer[start..start + first].copy_from_slice(head);
+        buffer[..tail.len()].copy_from_slice(tail);
+        if self.storage == Storage::Mirrored {
+            buffer[len + start..len + start + first].copy_from_slice(head);
+            buffer[len..len + tail.len()].copy_from_slice(tail);
+        }
+
+        self.writer_ptr = (start + input.len()) % len;
+    }
+
+    /// バッファをゼロで埋め、書き込みポインタを先頭に戻す
+    pub fn clear(&mut self) {
+        self.buffer.as_mut_slice().fill(T::zero());
+        self.writer_ptr = 0;
+    }
+
+    /// バッファと補間器の状態を初期化する
+    pub fn reset(&mut self)
+    where
+        I: StatefulInterpolator<T>,
+    {
+        self.clear();
+        self.interpolator.reset();
+    }
+
+    /// バッファ内容、書き込み位置と補間器の内部状態（Thiran補間の直前の出力など）を保存する
+    #[cfg(feature = "alloc")]
+    pub fn snapshot(&self) -> DelayLineSnapshot<T>
+    where
+        I: StatefulInterpolator<T>,
+    {
+        DelayLineSnapshot {
+            buffer: self.buffer.as_slice()[..self.len].to_vec(),
+            writer_ptr: self.writer_ptr,
+            interpolator: self.interpolator.state(),
+        }
+    }
+
+    /// 保存したバッファ内容と書き込み位置を復元する（アロケーションを行わない）
+    /// リングバッファ長はスナップショットに合わせる
+    /// `snapshot`: `snapshot`で保存した状態
+    /// 戻り値: 確保済みの長さを超える、書き込み位置が範囲外、補間器の種類が異なるなどの場合はエラー（状態は変更しない）
+    #[cfg(feature = "alloc")]
+    pub fn restore(&mut self, snapshot: &DelayLineSnapshot<T>) -> Result<(), Error>
+    where
+        I: StatefulInterpolator<T>,
+    {
+        let len = snapshot.buffer.len();
+        let pow2_mismatch = matches!(self.storage, Storage::PowerOfTwo(_)) && !len.is_power_of_two();
+        if len == 0 || len > self.capacity() || snapshot.writer_ptr >= len || pow2_mismatch {
+            return Err(Error::InvalidSnapshot);
+        }
+        self.interpolator.restore_state(&snapshot.interpolator)?;
+
+        let buffer = self.buffer.as_mut_slice();
+        buffer[..len].copy_from_slice(&snapshot.buffer);
+        match self.storage {
+            Storage::PowerOfTwo(_) => self.storage = Storage::PowerOfTwo(len - 1),
+            Storage::Mirrored => buffer[len..len * 2].copy_from_slice(&snapshot.buffer),
+            Storage::Modulo => {}
+        }
+        self.len = len;
+        self.writer_ptr = snapshot.writer_ptr;
+
+        Ok(())
+    }
+
+    /// 補間器への参照を取得
+    pub fn interpolator(&self) -> &I {
+        &self.interpolator
+    }
+
+    /// 補間器への可変参照を取得
+    pub fn interpolator_mut(&mut self) -> &mut I {
+        &mut self.interpolator
+    }
+
+    /// 遅延時間から読み出し位置を計算
     /// `delay`: 遅延時間（サンプル単位）
-    /// 戻り値: 読み出したサンプル
-    pub fn read_interpolated(&self, delay: T) -> T {
+    /// 戻り値: バッファ内の読み出し位置
+    fn read_position(&self, delay: T) -> f64 {
         let delay_float = delay.to_f64().unwrap();
-        let buffer_len = self.buffer.len() as f64;
+        let buffer_len = self.len() as f64;
         let writer_pos = self.writer_ptr as f64;
 
         // 読み出し位置の計算
@@ -43,7 +413,251 @@ impl<T: Float, I: Interpolator<T>> DelayLine<T, I> {
             read_pos += buffer_len;
         }
 
+        read_pos
+    }
+
+    /// 補間器を考慮した最大遅延時間（サンプル単位）
+    /// これを超える遅延はリングバッファ内で折り返し、古いサンプルを読んでしまう
+    pub fn max_delay(&self) -> usize
+    where
+        I: StatefulInterpolator<T>,
+    {
+        self.len().saturating_sub(self.interpolator.headroom())
+    }
+
+    /// 補間器を考慮した最小遅延時間（サンプル単位）
+    /// これより短い遅延は、これから書き込まれる位置の古いサンプルを読んでしまう
+    pub fn min_delay(&self) -> usize
+    where
+        I: StatefulInterpolator<T>,
+    {
+        1 + self.interpolator.lookahead()
+    }
+
+    /// 状態を持つ補間器で遅延線からサンプルを読み出す
+    /// `delay`: 遅延時間（サンプル単位）
+    /// 戻り値: 読み出したサンプル
+    pub fn read_interpolated_mut(&mut self, delay: T) -> T
+    where
+        I: StatefulInterpolator<T>,
+    {
+        let read_pos = self.read_position(delay);
+        let end = self.active_end();
+        read_with(self.storage, &self.buffer.as_slice()[..end], read_pos, &mut self.interpolator)
+    }
+
+    /// 遅延線が持つものとは別の補間器でサンプルを読み出す
+    /// 1つのバッファから補間方法の異なる複数のタップを読む場合に使う
+    /// `delay`: 遅延時間（サンプル単位）
+    /// `interpolator`: 補間方法
+    /// 戻り値: 読み出したサンプル
+    pub fn read_interpolated_with<J: StatefulInterpolator<T>>(&self, delay: T, interpolator: &mut J) -> T {
+        let read_pos = self.read_position(delay);
+        read_with(self.storage, &self.buffer.as_slice()[..self.active_end()], read_pos, interpolator)
+    }
+
+    /// サンプルごとの遅延時間で1ブロック分を読み出す
+    /// `output[n]`は、`n`サンプル書き込んだ後に`delays[n]`で読んだ値になる
+    /// 遅延が短く、ブロック内でまだ書き込まれていないサンプルに届く場合は
+    /// 1サンプルずつ`read_interpolated_mut`と`push`を呼ぶこと
+    /// `delays`: 各サンプルの遅延時間（サンプル単位、`max_delay`以下）
+    /// `output`: 出力先
+    pub fn read_block(&mut self, delays: &[T], output: &mut [T])
+    where
+        I: StatefulInterpolator<T>,
+    {
+        let len = self.len();
+        let len_float = len as f64;
+        let end = self.active_end();
+        let buffer = &self.buffer.as_slice()[..end];
+
+        let mut writer_pos = self.writer_ptr;
+        for (out, &delay) in output.iter_mut().zip(delays) {
+            let mut read_pos = writer_pos as f64 - delay.to_f64().unwrap();
+            while read_pos < 0.0 {
+                read_pos += len_float;
+            }
+            *out = read_with(self.storage, buffer, read_pos, &mut self.interpolator);
+
+            writer_pos += 1;
+            if writer_pos == len {
+                writer_pos = 0;
+            }
+        }
+    }
+
+    /// 一定の遅延時間で1ブロック分を読み出す
+    /// 補間器は小数部から求めた係数をブロック全体で使い回せる
+    /// `delay`: 遅延時間（サンプル単位、`max_delay`以下）
+    /// `output`: 出力先（`read_block`と同じく`output[n]`は`n`サンプル書き込んだ後の値）
+    pub fn read_block_constant(&mut self, delay: T, output: &mut [T])
+    where
+        I: StatefulInterpolator<T>,
+    {
+        let len = self.len();
+        let mut read_pos = self.writer_ptr as f64 - delay.to_f64().unwrap();
+        while read_pos < 0.0 {
+            read_pos += len as f64;
+        }
+
+        // 2の累乗バッファとミラーバッファも先頭len要素は通常のリングバッファと同じ並び
+        let buffer = &self.buffer.as_slice()[..len];
+        self.interpolator.interpolate_run_mut(buffer, read_pos, output);
+    }
+
+    /// 遅延時間を線形に変化させながら1ブロック分を読み出す
+    /// `output[n]`の遅延は`start_delay + (end_delay - start_delay) * n / output.len()`
+    /// `start_delay`: ブロック先頭の遅延時間（サンプル単位）
+    /// `end_delay`: 次のブロック先頭での遅延時間（サンプル単位）
+    /// `output`: 出力先
+    pub fn read_block_ramp(&mut self, start_delay: T, end_delay: T, output: &mut [T])
+    where
+        I: StatefulInterpolator<T>,
+    {
+        let len = self.len();
+        let len_float = len as f64;
+        let end = self.active_end();
+        let buffer = &self.buffer.as_slice()[..end];
+
+        let start = start_delay.to_f64().unwrap();
+        let step = (end_delay.to_f64().unwrap() - start) / output.len().max(1) as f64;
+
+        let mut writer_pos = self.writer_ptr;
+        for (n, out) in output.iter_mut().enumerate() {
+            let mut read_pos = writer_pos as f64 - (start + step * n as f64);
+            while read_pos < 0.0 {
+                read_pos += len_float;
+            }
+            *out = read_with(self.storage, buffer, read_pos, &mut self.interpolator);
+
+            writer_pos += 1;
+            if writer_pos == len {
+                writer_pos = 0;
+            }
+        }
+    }
+
+    /// 補間付きで遅延線からサンプルを読み出す
+    /// `delay`: 遅延時間（サンプル単位）
+    /// 戻り値: 読み出したサンプル
+    pub fn read_interpolated(&self, delay: T) -> T
+    where
+        I: Interpolator<T>,
+    {
+        let read_pos = self.read_position(delay);
+
         // 補間を使ってサンプルを取得
-        self.interpolator.interpolate(&self.buffer, read_pos)
+        let end = self.active_end();
+        let buffer = &self.buffer.as_slice()[..end];
+        match self.storage {
+            Storage::Modulo => self.interpolator.interpolate(buffer, read_pos),
+            Storage::PowerOfTwo(_) => self.interpolator.interpolate_pow2(buffer, read_pos),
+            Storage::Mirrored => self.interpolator.interpolate_mirrored(buffer, read_pos),
+        }
+    }
+}
+
+/// 時間単位の最大遅延を収めるのに必要なリングバッファ長
+fn len_for<T: Float>(max_delay: DelayTime<T>, sample_rate: T, headroom: usize) -> usize {
+    let samples = max_delay.to_samples(sample_rate).ceil().to_usize().unwrap_or(0);
+    samples.max(1) + headroom
+}
+
+/// 格納方式に応じた補間関数でサンプルを読み出す
+#[inline]
+fn read_with<T, J: StatefulInterpolator<T>>(storage: Storage, buffer: &[T], read_pos: f64, interpolator: &mut J) -> T {
+    match storage {
+        Storage::Modulo => interpolator.interpolate_mut(buffer, read_pos),
+        Storage::PowerOfTwo(_) => interpolator.interpolate_mut_pow2(buffer, read_pos),
+        Storage::Mirrored => interpolator.interpolate_mut_mirrored(buffer, read_pos),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::{Cubic, Linear};
+
+    /// 同じ遅延時間で1サンプルずつ読み書きした結果と比較する
+    fn assert_block_matches_single(mut block: DelayLine<f64, Cubic>, mut single: DelayLine<f64, Cubic>, delays: &[f64]) {
+        let input: Vec<f64> = (0..delays.len()).map(|i| ((i * 37 % 101) as f64 - 50.0) / 50.0).collect();
+        for _ in 0..3 {
+            block.write_block(&input);
+            for &x in &input {
+                single.push(x);
+            }
+        }
+
+        let mut output = vec![0.0; delays.len()];
+        block.read_block(delays, &mut output);
+        for (&delay, &out) in delays.iter().zip(&output) {
+            assert!((single.read_interpolated_mut(delay) - out).abs() < 1e-12);
+            single.push(0.0);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_write_block_matches_push() {
+        for make in [DelayLine::new, DelayLine::new_pow2, DelayLine::new_mirrored] {
+            let mut block = make(40, Linear);
+            let mut single = make(40, Linear);
+
+            // 折り返しをまたぐ書き込みと、バッファより長い書き込み
+            for len in [7, 29, 100, 3] {
+                let input: Vec<f64> = (0..len).map(|i| i as f64 + len as f64 * 0.5).collect();
+                block.write_block(&input);
+                for &x in &input {
+                    single.push(x);
+                }
+                for delay in 1..40 {
+                    assert_eq!(block.read_interpolated(delay as f64), single.read_interpolated(delay as f64));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_block_ramp_matches_read_block() {
+        let mut ramp = DelayLine::new(64, Cubic);
+        let mut block = DelayLine::new(64, Cubic);
+        let input: Vec<f64> = (0..64).map(|i| (i as f64 * 0.3).sin()).collect();
+        ramp.write_block(&input);
+        block.write_block(&input);
+
+        let delays: Vec<f64> = (0..16).map(|n| 40.0 + (12.5 - 40.0) * n as f64 / 16.0).collect();
+        let mut output_ramp = vec![0.0; 16];
+        let mut output_block = vec![0.0; 16];
+        ramp.read_block_ramp(40.0, 12.5, &mut output_ramp);
+        block.read_block(&delays, &mut output_block);
+        for (a, b) in output_ramp.iter().zip(&output_block) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_read_block_wraps_long_delays() {
+        // バッファ長を超える遅延も`read_interpolated_mut`と同じ位置で折り返す
+        let delays = [70.25, 135.5, 20.0, 99.75];
+        assert_block_matches_single(DelayLine::new(64, Cubic), DelayLine::new(64, Cubic), &delays);
+    }
+
+    #[test]
+    fn test_read_block_constant_wraps_long_delays() {
+        // バッファ長の2倍を超える遅延でも`read_block`と同じ位置を読む
+        for delay in [20.0, 70.25, 135.5, 200.75] {
+            let mut constant = DelayLine::new(64, Cubic);
+            let mut block = DelayLine::new(64, Cubic);
+            let input: Vec<f64> = (0..100).map(|i| (i as f64 * 0.3).sin()).collect();
+            constant.write_block(&input);
+            block.write_block(&input);
+
+            let mut output_constant = vec![0.0; 16];
+            let mut output_block = vec![0.0; 16];
+            constant.read_block_constant(delay, &mut output_constant);
+            block.read_block(&[delay; 16], &mut output_block);
+            for (a, b) in output_constant.iter().zip(&output_block) {
+                assert!((a - b).abs() < 1e-12);
+            }
+        }
+    }
+}
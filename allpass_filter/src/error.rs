@@ -0,0 +1,30 @@
+﻿use core::fmt;
+
+/// クレート共通のエラー型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// フィードバックゲインが安定範囲外（またはNaN）
+    UnstableGain,
+    /// 遅延バッファのサイズが補間に必要な長さに満たない
+    InvalidBufferSize,
+    /// 遅延時間が負、NaN、または最大遅延を超えている
+    DelayOutOfRange,
+    /// スナップショットの長さや書き込み位置が復元先と合わない
+    InvalidSnapshot,
+    /// イベントの位置がブロック外、または昇順に並んでいない
+    InvalidEvent,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnstableGain => write!(f, "feedback gain is outside the stable range"),
+            Error::InvalidBufferSize => write!(f, "delay buffer is too short for the interpolator"),
+            Error::DelayOutOfRange => write!(f, "delay time is outside the legal range"),
+            Error::InvalidSnapshot => write!(f, "snapshot does not fit the delay buffer"),
+            Error::InvalidEvent => write!(f, "event offset is outside the block or out of order"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
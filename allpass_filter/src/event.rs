@@ -0,0 +1,33 @@
+﻿use crate::time::DelayTime;
+
+/// イベントで変更するパラメータと新しい目標値
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamTarget<T> {
+    /// 遅延時間
+    Delay(DelayTime<T>),
+    /// フィードバックゲイン
+    Gain(T),
+}
+
+/// ブロック内の指定したサンプル位置でパラメータを変更するイベント
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamEvent<T> {
+    pub offset: usize,          // ブロック先頭からのサンプル位置
+    pub target: ParamTarget<T>, // 変更するパラメータと目標値
+}
+
+impl<T> ParamEvent<T> {
+    /// 遅延時間を変更するイベントを作成
+    /// `offset`: ブロック先頭からのサンプル位置
+    /// `delay`: 新しい遅延時間
+    pub fn delay(offset: usize, delay: DelayTime<T>) -> Self {
+        Self { offset, target: ParamTarget::Delay(delay) }
+    }
+
+    /// フィードバックゲインを変更するイベントを作成
+    /// `offset`: ブロック先頭からのサンプル位置
+    /// `gain`: 新しいフィードバックゲイン
+    pub fn gain(offset: usize, gain: T) -> Self {
+        Self { offset, target: ParamTarget::Gain(gain) }
+    }
+}
@@ -0,0 +1,142 @@
+﻿use num_traits::Float;
+use crate::parameter::{SmoothedParam, DEFAULT_SMOOTHING_TIME_MS};
+use crate::processor::Processor;
+
+/// 1次オールパスフィルタ
+/// 式: y[n] = -g x[n] + x[n - 1] + g y[n - 1]
+/// 折点周波数で位相が90度回転する
+pub struct FirstOrderAllPass<T> {
+    frequency: SmoothedParam<T>, // 折点周波数（Hz）
+    sample_rate: T,              // サンプルレート（Hz）
+    coeff_frequency: T,          // 係数計算に使用した折点周波数
+    g: T,                        // フィードバック / フィードフォワード係数
+    x1: T,                       // 1サンプル前の入力
+    y1: T,                       // 1サンプル前の出力
+}
+
+impl<T: Float> FirstOrderAllPass<T> {
+    /// 新しいFirstOrderAllPassを作成
+    /// `sample_rate`: サンプルレート（Hz）
+    /// `frequency`: 折点周波数（Hz）
+    pub fn new(sample_rate: T, frequency: T) -> Self {
+        let smoothing_time = T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap();
+
+        let mut filter = Self {
+            frequency: SmoothedParam::with_time_constant(frequency, smoothing_time, sample_rate),
+            sample_rate,
+            coeff_frequency: frequency,
+            g: T::zero(),
+            x1: T::zero(),
+            y1: T::zero(),
+        };
+        filter.update_coefficient(frequency);
+        filter
+    }
+
+    /// 平滑化係数を設定
+    /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
+    pub fn set_smoothing(&mut self, factor: T) {
+        self.frequency.set_factor(factor);
+    }
+
+    /// 折点周波数から係数gを再計算
+    /// `frequency`: 折点周波数（Hz）
+    fn update_coefficient(&mut self, frequency: T) {
+        let t = (T::from(core::f64::consts::PI).unwrap() * frequency / self.sample_rate).tan();
+        self.g = (T::one() - t) / (T::one() + t);
+        self.coeff_frequency = frequency;
+    }
+
+    /// 現在の係数gを取得
+    pub fn coefficient(&self) -> T {
+        self.g
+    }
+
+    /// オーディオサンプルを処理
+    /// `input`: 入力サンプル
+    /// 戻り値: 出力サンプル
+    #[inline]
+    pub fn process(&mut self, input: T) -> T {
+        let current_frequency = self.frequency.next();
+
+        // パラメータが動いている間だけ係数を再計算
+        if current_frequency != self.coeff_frequency {
+            self.update_coefficient(current_frequency);
+        }
+
+        let output = -self.g * input + self.x1 + self.g * self.y1;
+        self.x1 = input;
+        self.y1 = output;
+
+        output
+    }
+
+    /// ブロック単位でオーディオサンプルを処理
+    /// `input`: 入力サンプルのスライス
+    /// `output`: 出力サンプルのスライス
+    pub fn process_block(&mut self, input: &[T], output: &mut [T]) {
+        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+            *out_sample = self.process(*in_sample);
+        }
+    }
+
+    /// ブロック単位でオーディオサンプルをインプレース処理
+    /// `buffer`: 入出力サンプルのスライス
+    pub fn process_block_inplace(&mut self, buffer: &mut [T]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+
+    /// 状態変数をゼロにし、パラメータを目標値に合わせる
+    pub fn reset(&mut self) {
+        self.frequency.reset();
+        self.x1 = T::zero();
+        self.y1 = T::zero();
+    }
+
+    /// 折点周波数を設定
+    /// `frequency`: 折点周波数（Hz）
+    pub fn set_frequency(&mut self, frequency: T) {
+        self.frequency.set_target(frequency);
+    }
+}
+
+impl<T: Float> Processor<T> for FirstOrderAllPass<T> {
+    #[inline]
+    fn process(&mut self, input: T) -> T {
+        FirstOrderAllPass::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        FirstOrderAllPass::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarter_turn_at_break_frequency() {
+        let sample_rate = 48000.0;
+        let frequency = 2000.0;
+        let mut first_order = FirstOrderAllPass::new(sample_rate, frequency);
+
+        let mut impulse_response = vec![0.0; 8192];
+        impulse_response[0] = 1.0;
+        first_order.process_block_inplace(&mut impulse_response);
+
+        let omega = 2.0 * core::f64::consts::PI * frequency / sample_rate;
+        let (mut re, mut im) = (0.0, 0.0);
+        for (n, h) in impulse_response.iter().enumerate() {
+            re += h * (omega * n as f64).cos();
+            im -= h * (omega * n as f64).sin();
+        }
+
+        let magnitude = (re * re + im * im).sqrt();
+        let phase = im.atan2(re);
+        assert!((magnitude - 1.0).abs() < 1e-6, "Magnitude should be 1.0, got {}", magnitude);
+        assert!((phase + core::f64::consts::FRAC_PI_2).abs() < 1e-6, "Phase should be -90 degrees, got {}", phase);
+    }
+}
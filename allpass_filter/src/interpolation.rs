@@ -1,26 +1,246 @@
 ﻿use num_traits::Float;
+use crate::error::Error;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 pub trait Interpolator<T> {
     fn interpolate(&self, buffer: &[T], read_pos: f64) -> T;
+
+    /// バッファ長が2の累乗の場合の補間
+    /// インデックスの折り返しを剰余の代わりにビットマスクで行える
+    fn interpolate_pow2(&self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate(buffer, read_pos)
+    }
+
+    /// 各サンプルを2回書き込んだ2倍長バッファ（ミラーバッファ）からの補間
+    /// `buffer[i]`と`buffer[i + len]`が同じ値を持つため、カーネルの先頭を折り返すだけで
+    /// 連続したスライスとして読み出せる
+    fn interpolate_mirrored(&self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate(&buffer[..buffer.len() / 2], read_pos)
+    }
+
+    /// 小数部を保ったまま1サンプルずつ進む読み出し位置で連続して補間する
+    /// 係数が小数部だけで決まる補間器は、係数を一度だけ計算して使い回せる
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 先頭の読み出し位置（0以上バッファ長未満）
+    /// `output`: 出力先（`output[n]`は`read_pos + n`を補間した値）
+    fn interpolate_run(&self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        let len = buffer.len() as f64;
+        let mut pos = read_pos;
+        for out in output.iter_mut() {
+            *out = self.interpolate(buffer, pos);
+            pos += 1.0;
+            if pos >= len {
+                pos -= len;
+            }
+        }
+    }
+}
+
+/// ミラーバッファ内のカーネル先頭位置を求める
+/// `index_i`: 読み出し位置の整数部（0以上len未満）
+/// `before`: カーネルが読み出し位置より過去側に伸びるサンプル数
+/// `len`: リングバッファ長（ミラーバッファ長の半分）
+#[inline]
+fn mirrored_start(index_i: usize, before: usize, len: usize) -> usize {
+    let start = index_i + len - before;
+    if start >= len { start - len } else { start }
+}
+
+/// 内部状態を持つ補間器のためのトレイト
+/// 状態を持たない補間器は`Interpolator`の結果をそのまま返す
+pub trait StatefulInterpolator<T> {
+    fn interpolate_mut(&mut self, buffer: &[T], read_pos: f64) -> T;
+
+    /// バッファ長が2の累乗の場合の補間
+    fn interpolate_mut_pow2(&mut self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate_mut(buffer, read_pos)
+    }
+
+    /// ミラーバッファからの補間
+    fn interpolate_mut_mirrored(&mut self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate_mut(&buffer[..buffer.len() / 2], read_pos)
+    }
+
+    /// 1サンプルずつ進む読み出し位置で連続して補間する
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 先頭の読み出し位置（0以上バッファ長未満）
+    /// `output`: 出力先（`output[n]`は`read_pos + n`を補間した値）
+    fn interpolate_run_mut(&mut self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        let len = buffer.len() as f64;
+        let mut pos = read_pos;
+        for out in output.iter_mut() {
+            *out = self.interpolate_mut(buffer, pos);
+            pos += 1.0;
+            if pos >= len {
+                pos -= len;
+            }
+        }
+    }
+
+    /// 読み出し位置より過去側に追加で必要なサンプル数
+    /// 遅延線の最大遅延はバッファ長からこの値を引いたものになる
+    fn headroom(&self) -> usize {
+        0
+    }
+
+    /// 読み出し位置の整数部より未来側に参照するサンプル数
+    /// ブロック読み出しで、まだ書き込まれていないサンプルを読まないために使う
+    fn lookahead(&self) -> usize {
+        1
+    }
+
+    /// 内部状態を初期化する
+    fn reset(&mut self) {}
+
+    /// 内部状態を保存する（状態を持たない補間器は`Stateless`）
+    fn state(&self) -> InterpolatorState<T> {
+        InterpolatorState::Stateless
+    }
+
+    /// `state`を復元した後の`headroom`と`lookahead`
+    /// 状態によって参照範囲が変わる補間器は、復元前の検証のためにこれを上書きする
+    /// `state`: 復元する状態
+    fn range_for_state(&self, _state: &InterpolatorState<T>) -> (usize, usize) {
+        (self.headroom(), self.lookahead())
+    }
+
+    /// 保存した内部状態を復元する
+    /// `state`: `state`で保存した状態
+    /// 戻り値: 別の種類の補間器の状態の場合はエラー（状態は変更しない）
+    fn restore_state(&mut self, state: &InterpolatorState<T>) -> Result<(), Error> {
+        match state {
+            InterpolatorState::Stateless => Ok(()),
+            _ => Err(Error::InvalidSnapshot),
+        }
+    }
+}
+
+/// `StatefulInterpolator`の内部状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolatorState<T> {
+    /// 内部状態を持たない補間器
+    Stateless,
+    /// Thiran補間の直前の出力
+    Thiran { prev_output: T },
+    /// `DynamicInterpolator`の補間方法、クロスフェードの進行位置とThiran補間の直前の出力
+    Dynamic {
+        mode: InterpolationMode,
+        prev_mode: InterpolationMode,
+        fade_pos: usize,
+        prev_output: T,
+    },
+}
+
+/// 状態を持たない補間器に`StatefulInterpolator`を実装する
+macro_rules! impl_stateful_interpolator {
+    ($($name:ty => ($headroom:expr, $lookahead:expr)),*) => {
+        $(
+            impl<T: Float> StatefulInterpolator<T> for $name {
+                #[inline]
+                fn interpolate_mut(&mut self, buffer: &[T], read_pos: f64) -> T {
+                    self.interpolate(buffer, read_pos)
+                }
+
+                #[inline]
+                fn interpolate_mut_pow2(&mut self, buffer: &[T], read_pos: f64) -> T {
+                    self.interpolate_pow2(buffer, read_pos)
+                }
+
+                #[inline]
+                fn interpolate_mut_mirrored(&mut self, buffer: &[T], read_pos: f64) -> T {
+                    self.interpolate_mirrored(buffer, read_pos)
+                }
+
+                #[inline]
+                fn interpolate_run_mut(&mut self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+                    self.interpolate_run(buffer, read_pos, output)
+                }
+
+                fn headroom(&self) -> usize {
+                    $headroom
+                }
+
+                fn lookahead(&self) -> usize {
+                    $lookahead
+                }
+            }
+        )*
+    };
+}
+
+// Cubicは読み出し位置の1つ前と2つ先のサンプルも参照する
+impl_stateful_interpolator!(Linear => (0, 1), Nearest => (0, 1), Cubic => (1, 2));
+
+impl<T: Float, const N: usize> StatefulInterpolator<T> for Lagrange<N> {
+    #[inline]
+    fn interpolate_mut(&mut self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate(buffer, read_pos)
+    }
+
+    #[inline]
+    fn interpolate_mut_mirrored(&mut self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate_mirrored(buffer, read_pos)
+    }
+
+    #[inline]
+    fn interpolate_run_mut(&mut self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        self.interpolate_run(buffer, read_pos, output)
+    }
+
+    fn headroom(&self) -> usize {
+        N / 2
+    }
+
+    fn lookahead(&self) -> usize {
+        N - N / 2
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float> StatefulInterpolator<T> for Sinc<T> {
+    #[inline]
+    fn interpolate_mut(&mut self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate(buffer, read_pos)
+    }
+
+    #[inline]
+    fn interpolate_mut_mirrored(&mut self, buffer: &[T], read_pos: f64) -> T {
+        self.interpolate_mirrored(buffer, read_pos)
+    }
+
+    #[inline]
+    fn interpolate_run_mut(&mut self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        self.interpolate_run(buffer, read_pos, output)
+    }
+
+    fn headroom(&self) -> usize {
+        self.taps / 2 - 1
+    }
+
+    fn lookahead(&self) -> usize {
+        self.taps / 2
+    }
 }
 
 pub struct Linear;
 
-impl<T: Float> Interpolator<T> for Linear {
+impl Linear {
     /// 線形補間を行う
     /// `buffer`: 補間対象のリングバッファ
     /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// `wrap`: インデックスをバッファ内に折り返す関数
     /// 戻り値: 補間されたサンプル値
-    fn interpolate(&self, buffer: &[T], read_pos: f64) -> T {
-        let len = buffer.len();
-        
+    #[inline]
+    fn interpolate_with<T: Float>(buffer: &[T], read_pos: f64, wrap: impl Fn(usize) -> usize) -> T {
         // 整数部と小数部の分離
         let index_i = read_pos.floor() as usize;
         let frac = read_pos - (index_i as f64);
         
         // リングバッファのインデックス計算
-        let index0 = index_i % len;
-        let index1 = (index_i + 1) % len;
+        let index0 = wrap(index_i);
+        let index1 = wrap(index_i + 1);
         
         let value0 = buffer[index0];
         let value1 = buffer[index1];
@@ -31,6 +251,43 @@ impl<T: Float> Interpolator<T> for Linear {
     }
 }
 
+impl<T: Float> Interpolator<T> for Linear {
+    /// 線形補間を行う
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// 戻り値: 補間されたサンプル値
+    fn interpolate(&self, buffer: &[T], read_pos: f64) -> T {
+        let len = buffer.len();
+        Self::interpolate_with(buffer, read_pos, |index| index % len)
+    }
+
+    fn interpolate_pow2(&self, buffer: &[T], read_pos: f64) -> T {
+        let mask = buffer.len() - 1;
+        Self::interpolate_with(buffer, read_pos, |index| index & mask)
+    }
+
+    fn interpolate_mirrored(&self, buffer: &[T], read_pos: f64) -> T {
+        let index_i = read_pos.floor() as usize;
+        let frac_t = T::from(read_pos - (index_i as f64)).unwrap();
+
+        let window = &buffer[index_i..index_i + 2];
+        window[0] * (T::one() - frac_t) + window[1] * frac_t
+    }
+
+    fn interpolate_run(&self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        let len = buffer.len();
+        let mut index = read_pos.floor() as usize;
+        let frac_t = T::from(read_pos - (index as f64)).unwrap();
+        let weight0 = T::one() - frac_t;
+
+        for out in output.iter_mut() {
+            let next = if index + 1 == len { 0 } else { index + 1 };
+            *out = buffer[index] * weight0 + buffer[next] * frac_t;
+            index = next;
+        }
+    }
+}
+
 pub struct Nearest;
 
 impl<T: Float> Interpolator<T> for Nearest {
@@ -42,16 +299,38 @@ impl<T: Float> Interpolator<T> for Nearest {
         let index = (read_pos.round() as usize) % buffer.len();
         buffer[index]
     }
+
+    fn interpolate_pow2(&self, buffer: &[T], read_pos: f64) -> T {
+        let index = (read_pos.round() as usize) & (buffer.len() - 1);
+        buffer[index]
+    }
+
+    fn interpolate_mirrored(&self, buffer: &[T], read_pos: f64) -> T {
+        // 切り上げでlenになってもミラー側に同じ値がある
+        buffer[read_pos.round() as usize]
+    }
+
+    fn interpolate_run(&self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        let len = buffer.len();
+        let mut index = (read_pos.round() as usize) % len;
+
+        for out in output.iter_mut() {
+            *out = buffer[index];
+            index = if index + 1 == len { 0 } else { index + 1 };
+        }
+    }
 }
 
 pub struct Cubic;
 
-impl<T: Float> Interpolator<T> for Cubic {
+impl Cubic {
     /// 3次補間（Catmull-Romスプライン）を行う
     /// `buffer`: 補間対象のリングバッファ
     /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// `wrap`: インデックスをバッファ内に折り返す関数
     /// 戻り値: 補間されたサンプル値
-    fn interpolate(&self, buffer: &[T], read_pos: f64) -> T {
+    #[inline]
+    fn interpolate_with<T: Float>(buffer: &[T], read_pos: f64, wrap: impl Fn(usize) -> usize) -> T {
         let len = buffer.len();
         
         // 整数部と小数部の分離
@@ -61,16 +340,19 @@ impl<T: Float> Interpolator<T> for Cubic {
         
         // 4つの点のインデックス計算
         // p0: 一つ前の点, p1: 現在の点, p2: 次の点, p3: 次の次の点
-        let index0 = (index_i + len - 1) % len;
-        let index1 = index_i % len;
-        let index2 = (index_i + 1) % len;
-        let index3 = (index_i + 2) % len;
-        
-        let p0 = buffer[index0];
-        let p1 = buffer[index1];
-        let p2 = buffer[index2];
-        let p3 = buffer[index3];
+        let index0 = wrap(index_i + len - 1);
+        let index1 = wrap(index_i);
+        let index2 = wrap(index_i + 1);
+        let index3 = wrap(index_i + 2);
         
+        Self::catmull_rom(buffer[index0], buffer[index1], buffer[index2], buffer[index3], frac_t)
+    }
+
+    /// 4点からCatmull-Romスプラインを評価する
+    /// `p0`, `p1`, `p2`, `p3`: 一つ前の点、現在の点、次の点、次の次の点
+    /// `frac_t`: p1から見た小数部
+    #[inline]
+    fn catmull_rom<T: Float>(p0: T, p1: T, p2: T, p3: T, frac_t: T) -> T {
         // Catmull-Romのスプライン補間
         let c0 = p1;
         let c1 = (p2 - p0) * T::from(0.5).unwrap();
@@ -88,4 +370,574 @@ impl<T: Float> Interpolator<T> for Cubic {
         // ホーナー法による多項式評価
         ((c3 * frac_t + c2) * frac_t + c1) * frac_t + c0
     }
+}
+
+impl<T: Float> Interpolator<T> for Cubic {
+    /// 3次補間（Catmull-Romスプライン）を行う
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// 戻り値: 補間されたサンプル値
+    fn interpolate(&self, buffer: &[T], read_pos: f64) -> T {
+        let len = buffer.len();
+        Self::interpolate_with(buffer, read_pos, |index| index % len)
+    }
+
+    fn interpolate_pow2(&self, buffer: &[T], read_pos: f64) -> T {
+        let mask = buffer.len() - 1;
+        Self::interpolate_with(buffer, read_pos, |index| index & mask)
+    }
+
+    fn interpolate_mirrored(&self, buffer: &[T], read_pos: f64) -> T {
+        let len = buffer.len() / 2;
+        let index_i = read_pos.floor() as usize;
+        let frac_t = T::from(read_pos - (index_i as f64)).unwrap();
+
+        // 4点を1つの連続したスライスとして読む
+        let start = mirrored_start(index_i, 1, len);
+        let window = &buffer[start..start + 4];
+        Self::catmull_rom(window[0], window[1], window[2], window[3], frac_t)
+    }
+
+    fn interpolate_run(&self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        let len = buffer.len();
+        let mut index = read_pos.floor() as usize;
+        let t = T::from(read_pos - (index as f64)).unwrap();
+
+        // Catmull-Romスプラインを4点の重みとして一度だけ計算する
+        let half = T::from(0.5).unwrap();
+        let (t2, t3) = (t * t, t * t * t);
+        let weight0 = half * (-t3 + t2 + t2 - t);
+        let weight1 = half * (T::from(3.0).unwrap() * t3 - T::from(5.0).unwrap() * t2 + T::from(2.0).unwrap());
+        let weight2 = half * (T::from(-3.0).unwrap() * t3 + T::from(4.0).unwrap() * t2 + t);
+        let weight3 = half * (t3 - t2);
+
+        for out in output.iter_mut() {
+            let index0 = if index == 0 { len - 1 } else { index - 1 };
+            let index2 = if index + 1 == len { 0 } else { index + 1 };
+            let index3 = (index + 2) % len;
+            *out = weight0 * buffer[index0] + weight1 * buffer[index] + weight2 * buffer[index2] + weight3 * buffer[index3];
+            index = index2;
+        }
+    }
+}
+
+/// N次ラグランジュ補間器 (N = 1..=7)
+/// N + 1点のカーネルを読み出し位置の中心に配置する
+/// Nが大きいほど通過域が平坦になるが、計算コストも増える
+pub struct Lagrange<const N: usize>;
+
+impl<T: Float, const N: usize> Interpolator<T> for Lagrange<N> {
+    /// N次ラグランジュ補間を行う
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// 戻り値: 補間されたサンプル値
+    fn interpolate(&self, buffer: &[T], read_pos: f64) -> T {
+        const { assert!(N >= 1 && N <= 7, "Lagrange order must be between 1 and 7") };
+
+        let len = buffer.len() as isize;
+
+        // カーネルの先頭位置と、先頭から見た読み出し位置
+        // xは常に[(N - 1) / 2, (N + 1) / 2)に収まり、カーネルの中心N / 2付近になる
+        let start = (read_pos - (N as f64 - 1.0) * 0.5).floor();
+        let x = read_pos - start;
+        let start = start as isize;
+
+        let mut output = T::zero();
+        for k in 0..=N {
+            let index = (start + k as isize).rem_euclid(len) as usize;
+            output = output + buffer[index] * T::from(Self::weight(k, x)).unwrap();
+        }
+
+        output
+    }
+
+    fn interpolate_mirrored(&self, buffer: &[T], read_pos: f64) -> T {
+        const { assert!(N >= 1 && N <= 7, "Lagrange order must be between 1 and 7") };
+
+        let len = buffer.len() / 2;
+        let index_i = read_pos.floor();
+        let start = (read_pos - (N as f64 - 1.0) * 0.5).floor();
+        let x = read_pos - start;
+
+        // N + 1点を1つの連続したスライスとして読む
+        let start = mirrored_start(index_i as usize, (index_i - start) as usize, len);
+        buffer[start..start + N + 1]
+            .iter()
+            .enumerate()
+            .fold(T::zero(), |output, (k, &value)| output + value * T::from(Self::weight(k, x)).unwrap())
+    }
+
+    fn interpolate_run(&self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        const { assert!(N >= 1 && N <= 7, "Lagrange order must be between 1 and 7") };
+
+        let len = buffer.len();
+        let start = (read_pos - (N as f64 - 1.0) * 0.5).floor();
+        let x = read_pos - start;
+        let start = (start as isize).rem_euclid(len as isize) as usize;
+
+        // 小数部はラン全体で変わらないため、各点の重みを一度だけ計算してタップごとに足し込む
+        output.fill(T::zero());
+        for k in 0..=N {
+            let weight = T::from(Self::weight(k, x)).unwrap();
+            let mut index = (start + k) % len;
+            for out in output.iter_mut() {
+                *out = *out + buffer[index] * weight;
+                index = if index + 1 == len { 0 } else { index + 1 };
+            }
+        }
+    }
+}
+
+impl<const N: usize> Lagrange<N> {
+    /// k番目の点に対するラグランジュ基底多項式の値
+    /// h_k = Π_{j≠k} (x - j) / (k - j)
+    #[inline]
+    fn weight(k: usize, x: f64) -> f64 {
+        let mut weight = 1.0;
+        for j in 0..=N {
+            if j != k {
+                weight *= (x - j as f64) / (k as f64 - j as f64);
+            }
+        }
+        weight
+    }
+}
+
+/// カイザー窓付きsinc関数による帯域制限補間器
+/// 係数は生成時にポリフェーズテーブルとして計算し、補間時は小数部でテーブルを引く
+/// 読み出し位置の前後に`taps / 2`サンプルずつ必要になる
+#[cfg(feature = "alloc")]
+pub struct Sinc<T> {
+    taps: usize,   // タップ数（偶数）
+    phases: usize, // 小数部の分割数
+    table: Vec<T>, // 係数テーブル [(phases + 1) * taps]
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float> Sinc<T> {
+    /// 新しいSincを作成
+    /// `taps`: タップ数（2以上の偶数）
+    /// `phases`: 小数部の分割数
+    /// `beta`: カイザー窓の形状パラメータ（大きいほど阻止域減衰が大きく、遷移帯域が広い）
+    pub fn new(taps: usize, phases: usize, beta: f64) -> Self {
+        assert!(taps >= 2 && taps.is_multiple_of(2), "Sinc taps must be an even number >= 2");
+        assert!(phases >= 1, "Sinc phases must be >= 1");
+
+        let half = (taps / 2) as f64;
+        let center = (taps / 2 - 1) as f64;
+        let i0_beta = bessel_i0(beta);
+
+        // 小数部0.0から1.0までのphases + 1行を作成し、行間は線形補間する
+        let mut table = Vec::with_capacity((phases + 1) * taps);
+        let mut row = Vec::with_capacity(taps);
+        for p in 0..=phases {
+            let frac = p as f64 / phases as f64;
+
+            row.clear();
+            for k in 0..taps {
+                // 読み出し位置から見たタップの位置
+                let t = k as f64 - center - frac;
+                let ratio = t / half;
+                let window = if ratio.abs() <= 1.0 {
+                    bessel_i0(beta * (1.0 - ratio * ratio).sqrt()) / i0_beta
+                } else {
+                    0.0
+                };
+                row.push(sinc(t) * window);
+            }
+
+            // 直流ゲインが1になるように正規化
+            let sum: f64 = row.iter().sum();
+            table.extend(row.iter().map(|c| T::from(c / sum).unwrap()));
+        }
+
+        Self { taps, phases, table }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float> Default for Sinc<T> {
+    /// 16タップ、512位相、β = 8.0のSincを作成
+    fn default() -> Self {
+        Self::new(16, 512, 8.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float> Interpolator<T> for Sinc<T> {
+    /// ポリフェーズテーブルによる帯域制限補間を行う
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// 戻り値: 補間されたサンプル値
+    fn interpolate(&self, buffer: &[T], read_pos: f64) -> T {
+        let len = buffer.len();
+
+        // 整数部と小数部の分離
+        let index_i = read_pos.floor() as usize;
+        let frac = read_pos - (index_i as f64);
+
+        // 隣接する2つの位相行とその間の比率
+        let phase_pos = frac * self.phases as f64;
+        let phase = (phase_pos as usize).min(self.phases - 1);
+        let phase_frac = T::from(phase_pos - phase as f64).unwrap();
+        let row0 = &self.table[phase * self.taps..(phase + 1) * self.taps];
+        let row1 = &self.table[(phase + 1) * self.taps..(phase + 2) * self.taps];
+
+        // 先頭タップのインデックス
+        let start = index_i + len - (self.taps / 2 - 1);
+
+        let mut output = T::zero();
+        for k in 0..self.taps {
+            let coeff = row0[k] + (row1[k] - row0[k]) * phase_frac;
+            output = output + buffer[(start + k) % len] * coeff;
+        }
+
+        output
+    }
+
+    fn interpolate_mirrored(&self, buffer: &[T], read_pos: f64) -> T {
+        let len = buffer.len() / 2;
+
+        // 整数部と小数部の分離
+        let index_i = read_pos.floor() as usize;
+        let frac = read_pos - (index_i as f64);
+
+        // 隣接する2つの位相行とその間の比率
+        let phase_pos = frac * self.phases as f64;
+        let phase = (phase_pos as usize).min(self.phases - 1);
+        let phase_frac = T::from(phase_pos - phase as f64).unwrap();
+        let row0 = &self.table[phase * self.taps..(phase + 1) * self.taps];
+        let row1 = &self.table[(phase + 1) * self.taps..(phase + 2) * self.taps];
+
+        // 全タップを1つの連続したスライスとして読む
+        let start = mirrored_start(index_i, self.taps / 2 - 1, len);
+        let window = &buffer[start..start + self.taps];
+
+        window
+            .iter()
+            .zip(row0.iter().zip(row1.iter()))
+            .fold(T::zero(), |output, (&value, (&c0, &c1))| output + value * (c0 + (c1 - c0) * phase_frac))
+    }
+
+    fn interpolate_run(&self, buffer: &[T], read_pos: f64, output: &mut [T]) {
+        let len = buffer.len();
+
+        // 整数部と小数部の分離
+        let index_i = read_pos.floor() as usize;
+        let frac = read_pos - (index_i as f64);
+
+        // 隣接する2つの位相行とその間の比率
+        let phase_pos = frac * self.phases as f64;
+        let phase = (phase_pos as usize).min(self.phases - 1);
+        let phase_frac = T::from(phase_pos - phase as f64).unwrap();
+        let row0 = &self.table[phase * self.taps..(phase + 1) * self.taps];
+        let row1 = &self.table[(phase + 1) * self.taps..(phase + 2) * self.taps];
+
+        // 先頭タップのインデックス
+        let start = index_i + len - (self.taps / 2 - 1);
+
+        // 小数部はラン全体で変わらないため、係数の位相補間をタップごとに一度だけ行って足し込む
+        output.fill(T::zero());
+        for (k, (&c0, &c1)) in row0.iter().zip(row1.iter()).enumerate() {
+            let coeff = c0 + (c1 - c0) * phase_frac;
+            let mut index = (start + k) % len;
+            for out in output.iter_mut() {
+                *out = *out + buffer[index] * coeff;
+                index = if index + 1 == len { 0 } else { index + 1 };
+            }
+        }
+    }
+}
+
+/// 正規化sinc関数 sin(πx) / (πx)
+#[cfg(feature = "alloc")]
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        let pi_x = core::f64::consts::PI * x;
+        pi_x.sin() / pi_x
+    }
+}
+
+/// 第1種変形ベッセル関数 I0（級数展開）
+#[cfg(feature = "alloc")]
+fn bessel_i0(x: f64) -> f64 {
+    let half_x = x * 0.5;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= (half_x / k) * (half_x / k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// 1次Thiranオールパス補間器
+/// 振幅特性が平坦なため、線形補間のような高域の減衰が起こらない
+/// 前回の出力を保持するため、遅延時間を連続的に読み出す用途に限る
+/// 小数部が0.5を超える位置では整数部の2つ先のサンプルを参照するため、遅延時間は1.5サンプル以上にすること
+/// （それより短いと、これから書き込まれる位置の古いサンプルを読んでしまう）
+pub struct Thiran<T> {
+    prev_output: T, // 1サンプル前の出力
+}
+
+impl<T: Float> Thiran<T> {
+    /// 新しいThiranを作成
+    pub fn new() -> Self {
+        Self {
+            prev_output: T::zero(),
+        }
+    }
+}
+
+impl<T: Float> Default for Thiran<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> StatefulInterpolator<T> for Thiran<T> {
+    /// 1次オールパスによる小数遅延補間を行う
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// 戻り値: 補間されたサンプル値
+    fn interpolate_mut(&mut self, buffer: &[T], read_pos: f64) -> T {
+        let len = buffer.len();
+
+        // 整数部と小数部の分離
+        let index_i = read_pos.floor() as usize;
+        let frac = read_pos - (index_i as f64);
+
+        // 小数遅延Δを[0.5, 1.5]に収めて極を単位円から遠ざける
+        let (index_old, delta) = if frac > 0.5 {
+            (index_i + 1, 2.0 - frac)
+        } else {
+            (index_i, 1.0 - frac)
+        };
+
+        let x_old = buffer[index_old % len];
+        let x_new = buffer[(index_old + 1) % len];
+
+        // y[n] = η x[n] + x[n - 1] - η y[n - 1]
+        let eta = T::from((1.0 - delta) / (1.0 + delta)).unwrap();
+        let output = eta * x_new + x_old - eta * self.prev_output;
+        self.prev_output = output;
+
+        output
+    }
+
+    /// 小数部が0.5を超える場合は整数部の2つ先のサンプルを参照する（最小遅延1.5サンプルに相当）
+    fn lookahead(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.prev_output = T::zero();
+    }
+
+    fn state(&self) -> InterpolatorState<T> {
+        InterpolatorState::Thiran { prev_output: self.prev_output }
+    }
+
+    fn restore_state(&mut self, state: &InterpolatorState<T>) -> Result<(), Error> {
+        match *state {
+            InterpolatorState::Thiran { prev_output } => {
+                self.prev_output = prev_output;
+                Ok(())
+            }
+            _ => Err(Error::InvalidSnapshot),
+        }
+    }
+}
+
+/// 実行時に切り替え可能な補間方法
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationMode {
+    Nearest,
+    Linear,
+    Cubic,
+    Sinc,
+    Thiran,
+}
+
+/// 切り替え時のクロスフェード長の既定値（サンプル単位）
+pub const DEFAULT_CROSSFADE_SAMPLES: usize = 64;
+
+/// `DynamicInterpolator`が使うSincのタップ数（`Sinc::default()`と同じ）
+#[cfg(feature = "alloc")]
+const DYNAMIC_SINC_TAPS: usize = 16;
+
+/// `InterpolationMode`に従って補間方法を切り替える補間器
+/// 切り替え時は旧方式と新方式の出力をクロスフェードしてクリックを防ぐ
+/// ヘッドルームと先読みは使用中の補間方法（クロスフェード中は切り替え元も含む）に合わせる
+/// 遅延バッファには触れないため、切り替えによる遅延バッファの再確保は発生しない
+/// （Sincの係数テーブルは初めてSincを使うときに確保する）
+#[cfg(feature = "alloc")]
+pub struct DynamicInterpolator<T> {
+    mode: InterpolationMode,      // 現在の補間方法
+    prev_mode: InterpolationMode, // クロスフェード元の補間方法
+    fade_pos: usize,              // クロスフェードの進行位置
+    fade_len: usize,              // クロスフェード長（サンプル単位）
+    sinc: Option<Sinc<T>>,        // Sinc補間の係数テーブル（Sincを使うまではNone）
+    thiran: Thiran<T>,            // Thiran補間の内部状態
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float> DynamicInterpolator<T> {
+    /// 新しいDynamicInterpolatorを作成
+    /// `mode`: 初期の補間方法
+    pub fn new(mode: InterpolationMode) -> Self {
+        Self::with_crossfade(mode, DEFAULT_CROSSFADE_SAMPLES)
+    }
+
+    /// クロスフェード長を指定してDynamicInterpolatorを作成
+    /// `mode`: 初期の補間方法
+    /// `crossfade_samples`: 切り替え時のクロスフェード長（サンプル単位）
+    pub fn with_crossfade(mode: InterpolationMode, crossfade_samples: usize) -> Self {
+        let mut interpolator = Self {
+            mode,
+            prev_mode: mode,
+            fade_pos: crossfade_samples,
+            fade_len: crossfade_samples,
+            sinc: None,
+            thiran: Thiran::new(),
+        };
+        interpolator.prepare(mode);
+        interpolator
+    }
+
+    /// 補間方法を切り替える
+    /// 初めてSincに切り替えるときは係数テーブルを確保する
+    /// `mode`: 新しい補間方法
+    pub fn set_mode(&mut self, mode: InterpolationMode) {
+        if mode == self.mode {
+            return;
+        }
+
+        self.prepare(mode);
+        self.prev_mode = self.mode;
+        self.mode = mode;
+        self.fade_pos = 0;
+    }
+
+    /// 補間方法に必要な係数テーブルを確保する
+    fn prepare(&mut self, mode: InterpolationMode) {
+        if mode == InterpolationMode::Sinc && self.sinc.is_none() {
+            self.sinc = Some(Self::new_sinc());
+        }
+    }
+
+    /// Sinc補間の係数テーブルを作成
+    fn new_sinc() -> Sinc<T> {
+        Sinc::new(DYNAMIC_SINC_TAPS, 512, 8.0)
+    }
+
+    /// 補間方法ごとのヘッドルームと先読み
+    fn mode_range(mode: InterpolationMode) -> (usize, usize) {
+        match mode {
+            InterpolationMode::Nearest | InterpolationMode::Linear => (0, 1),
+            InterpolationMode::Cubic => (1, 2),
+            InterpolationMode::Sinc => (DYNAMIC_SINC_TAPS / 2 - 1, DYNAMIC_SINC_TAPS / 2),
+            InterpolationMode::Thiran => (0, 2),
+        }
+    }
+
+    /// 使用中の補間方法（クロスフェード中は切り替え元も含む）のヘッドルームと先読み
+    fn active_range(mode: InterpolationMode, prev_mode: InterpolationMode, fading: bool) -> (usize, usize) {
+        let (headroom, lookahead) = Self::mode_range(mode);
+        if !fading {
+            return (headroom, lookahead);
+        }
+        let (prev_headroom, prev_lookahead) = Self::mode_range(prev_mode);
+        (headroom.max(prev_headroom), lookahead.max(prev_lookahead))
+    }
+
+    /// 現在の補間方法を取得
+    pub fn mode(&self) -> InterpolationMode {
+        self.mode
+    }
+
+    /// 指定した補間方法で補間する
+    fn interpolate_with(&mut self, mode: InterpolationMode, buffer: &[T], read_pos: f64) -> T {
+        match mode {
+            InterpolationMode::Nearest => Nearest.interpolate(buffer, read_pos),
+            InterpolationMode::Linear => Linear.interpolate(buffer, read_pos),
+            InterpolationMode::Cubic => Cubic.interpolate(buffer, read_pos),
+            InterpolationMode::Sinc => self.sinc.get_or_insert_with(Self::new_sinc).interpolate(buffer, read_pos),
+            InterpolationMode::Thiran => self.thiran.interpolate_mut(buffer, read_pos),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Float> StatefulInterpolator<T> for DynamicInterpolator<T> {
+    /// 現在の補間方法で補間を行う（切り替え直後はクロスフェードする）
+    /// `buffer`: 補間対象のリングバッファ
+    /// `read_pos`: 読み出し位置（小数点以下を含む）
+    /// 戻り値: 補間されたサンプル値
+    fn interpolate_mut(&mut self, buffer: &[T], read_pos: f64) -> T {
+        let current = self.interpolate_with(self.mode, buffer, read_pos);
+        if self.fade_pos >= self.fade_len {
+            return current;
+        }
+
+        let previous = self.interpolate_with(self.prev_mode, buffer, read_pos);
+        let ratio = T::from(self.fade_pos as f64 / self.fade_len as f64).unwrap();
+        self.fade_pos += 1;
+
+        previous + (current - previous) * ratio
+    }
+
+    /// 使用中の補間方法のうち最大のヘッドルーム
+    fn headroom(&self) -> usize {
+        Self::active_range(self.mode, self.prev_mode, self.fade_pos < self.fade_len).0
+    }
+
+    /// 使用中の補間方法のうち最大の先読み
+    fn lookahead(&self) -> usize {
+        Self::active_range(self.mode, self.prev_mode, self.fade_pos < self.fade_len).1
+    }
+
+    /// 進行中のクロスフェードを終了し、Thiran補間の状態を初期化する
+    fn reset(&mut self) {
+        self.fade_pos = self.fade_len;
+        self.thiran.reset();
+    }
+
+    fn state(&self) -> InterpolatorState<T> {
+        InterpolatorState::Dynamic {
+            mode: self.mode,
+            prev_mode: self.prev_mode,
+            fade_pos: self.fade_pos,
+            prev_output: self.thiran.prev_output,
+        }
+    }
+
+    fn range_for_state(&self, state: &InterpolatorState<T>) -> (usize, usize) {
+        match *state {
+            InterpolatorState::Dynamic { mode, prev_mode, fade_pos, .. } => Self::active_range(mode, prev_mode, fade_pos < self.fade_len),
+            _ => (self.headroom(), self.lookahead()),
+        }
+    }
+
+    /// クロスフェードの進行位置はこの補間器のクロスフェード長に収める
+    /// Sincを使う状態を初めて復元するときは係数テーブルを確保する
+    fn restore_state(&mut self, state: &InterpolatorState<T>) -> Result<(), Error> {
+        match *state {
+            InterpolatorState::Dynamic { mode, prev_mode, fade_pos, prev_output } => {
+                self.prepare(mode);
+                self.prepare(prev_mode);
+                self.mode = mode;
+                self.prev_mode = prev_mode;
+                self.fade_pos = fade_pos.min(self.fade_len);
+                self.thiran.prev_output = prev_output;
+                Ok(())
+            }
+            _ => Err(Error::InvalidSnapshot),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,255 @@
+﻿use num_traits::Float;
+use crate::delay::DelayLine;
+use crate::error::Error;
+use crate::interpolation::{StatefulInterpolator, Linear};
+use crate::parameter::{SmoothedParam, DEFAULT_SMOOTHING_TIME_MS};
+use crate::processor::Processor;
+use crate::stability::GainGuard;
+use crate::time::DEFAULT_SAMPLE_RATE;
+
+/// ラティス型オールパスの構造
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatticeForm {
+    /// 1乗算器ラティス
+    /// t[n] = k (x[n] - s[n]), v[n] = x[n] + t[n], y[n] = s[n] + t[n]
+    OneMultiplier,
+    /// 正規化ラダー（状態のエネルギーを保存する回転構造）
+    /// y[n] = k x[n] + c s[n], v[n] = c x[n] - k s[n], c = sqrt(1 - k^2)
+    NormalizedLadder,
+}
+
+/// ラティス型のオールパスフィルタ
+/// s[n] = v[n - D]として、伝達関数は`AllPassFilter`と同じ (k + z^-D) / (1 + k z^-D) になる
+/// 反射係数kとフィードバックゲインgの関係は k = -g
+pub struct LatticeAllPass<T, I> {
+    delay_line: DelayLine<T, I>,    // 遅延線
+    delay_length: SmoothedParam<T>, // 遅延時間（サンプル単位）
+    k: SmoothedParam<T>,            // 反射係数
+    gain_guard: GainGuard<T>,       // 反射係数の安定化ガード
+    form: LatticeForm,              // ラティスの構造
+    coeff_k: T,                     // cの計算に使用した反射係数
+    c: T,                           // 正規化ラダーの係数 sqrt(1 - k^2)
+}
+
+impl<T: Float> LatticeAllPass<T, Linear> {
+    pub fn new_default(max_delay_samples: usize, initial_delay: T, gain: T, form: LatticeForm) -> Self {
+        Self::new(max_delay_samples, initial_delay, gain, form, Linear)
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>> LatticeAllPass<T, I> {
+    /// 新しいLatticeAllPassを作成
+    /// `max_delay_samples`: 最大遅延サンプル数
+    /// `initial_delay`: 初期遅延時間（サンプル単位）
+    /// `gain`: フィードバックゲイン（`AllPassFilter`のgと同じ意味。安定範囲にクランプされ、NaNは0になる）
+    /// `form`: ラティスの構造
+    /// `interpolator`: 補間方法
+    pub fn new(max_delay_samples: usize, initial_delay: T, gain: T, form: LatticeForm, interpolator: I) -> Self {
+        let smoothing_time = T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap();
+        let sample_rate = T::from(DEFAULT_SAMPLE_RATE).unwrap();
+        let gain_guard = GainGuard::default();
+        let k = -gain_guard.apply(gain).unwrap_or(T::zero());
+
+        Self {
+            delay_line: DelayLine::new(max_delay_samples, interpolator),
+            delay_length: SmoothedParam::with_time_constant(initial_delay, smoothing_time, sample_rate),
+            k: SmoothedParam::with_time_constant(k, smoothing_time, sample_rate),
+            gain_guard,
+            form,
+            coeff_k: k,
+            c: (T::one() - k * k).sqrt(),
+        }
+    }
+
+    /// 平滑化係数を設定
+    /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
+    pub fn set_smoothing(&mut self, factor: T) {
+        self.delay_length.set_factor(factor);
+        self.k.set_factor(factor);
+    }
+
+    /// オーディオサンプルを処理
+    /// `input`: 入力サンプル
+    /// 戻り値: 出力サンプル
+    #[inline]
+    pub fn process(&mut self, input: T) -> T {
+        let current_delay = self.delay_length.next();
+        let current_k = self.k.next();
+
+        let delayed_value = self.delay_line.read_interpolated_mut(current_delay);
+
+        let (v_n, output) = match self.form {
+            LatticeForm::OneMultiplier => {
+                let t = current_k * (input - delayed_value);
+                (input + t, delayed_value + t)
+            }
+            LatticeForm::NormalizedLadder => {
+                // 反射係数が動いている間だけcを再計算
+                if current_k != self.coeff_k {
+                    self.c = (T::one() - current_k * current_k).sqrt();
+                    self.coeff_k = current_k;
+                }
+                (
+                    self.c * input - current_k * delayed_value,
+                    current_k * input + self.c * delayed_value,
+                )
+            }
+        };
+
+        self.delay_line.push(v_n);
+
+        output
+    }
+
+    /// ブロック単位でオーディオサンプルを処理
+    /// `input`: 入力サンプルのスライス
+    /// `output`: 出力サンプルのスライス
+    pub fn process_block(&mut self, input: &[T], output: &mut [T]) {
+        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+            *out_sample = self.process(*in_sample);
+        }
+    }
+
+    /// ブロック単位でオーディオサンプルをインプレース処理
+    /// `buffer`: 入出力サンプルのスライス
+    pub fn process_block_inplace(&mut self, buffer: &mut [T]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+
+    /// 遅延線をゼロで埋め、パラメータを目標値に合わせる
+    pub fn reset(&mut self) {
+        self.delay_line.reset();
+        self.delay_length.reset();
+        self.k.reset();
+    }
+
+    /// 遅延時間を設定
+    /// `delay`: 遅延時間（サンプル単位）
+    pub fn set_delay(&mut self, delay: T) {
+        self.delay_length.set_target(delay);
+    }
+
+    /// フィードバックゲインを設定（反射係数は -gain になる）
+    /// `gain`: フィードバックゲイン
+    /// 戻り値: Rejectで範囲外の場合はエラー（目標値は変更しない）
+    pub fn set_gain(&mut self, gain: T) -> Result<(), Error> {
+        let gain = self.gain_guard.apply(gain)?;
+        self.k.set_target(-gain);
+        Ok(())
+    }
+
+    /// 反射係数を設定
+    /// ガードのポリシーに従って|k| < 1に収めた値を目標値にする
+    /// `k`: 反射係数
+    /// 戻り値: Rejectで範囲外の場合はエラー（目標値は変更しない）
+    pub fn set_reflection_coefficient(&mut self, k: T) -> Result<(), Error> {
+        let k = self.gain_guard.apply(k)?;
+        self.k.set_target(k);
+        Ok(())
+    }
+
+    /// 反射係数の安定化ガードを設定
+    /// `gain_guard`: 以降の`set_gain`と`set_reflection_coefficient`に適用するガード
+    pub fn set_gain_guard(&mut self, gain_guard: GainGuard<T>) {
+        self.gain_guard = gain_guard;
+    }
+
+    /// 反射係数の安定化ガードを取得
+    pub fn gain_guard(&self) -> GainGuard<T> {
+        self.gain_guard
+    }
+
+    /// 現在の反射係数を取得
+    pub fn reflection_coefficient(&self) -> T {
+        self.k.current()
+    }
+
+    /// ラティスの構造を取得
+    pub fn form(&self) -> LatticeForm {
+        self.form
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>> Processor<T> for LatticeAllPass<T, I> {
+    #[inline]
+    fn process(&mut self, input: T) -> T {
+        LatticeAllPass::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        LatticeAllPass::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allpass::AllPassFilter;
+    use crate::stability::GainPolicy;
+
+    /// 同じ入力に対して直接形と出力を比較する
+    fn assert_matches_direct_form(form: LatticeForm) {
+        let mut direct = AllPassFilter::new_default(100, 9.25, 0.6);
+        let mut lattice = LatticeAllPass::new_default(100, 9.25, 0.6, form);
+
+        for i in 0..256 {
+            let input = if i % 50 == 0 { 1.0 } else { 0.0 };
+            let out_direct = direct.process(input);
+            let out_lattice = lattice.process(input);
+            assert!((out_direct - out_lattice).abs() < 1e-9, "{:?} lattice should match direct form", form);
+        }
+    }
+
+    #[test]
+    fn test_one_multiplier_matches_direct_form() {
+        assert_matches_direct_form(LatticeForm::OneMultiplier);
+    }
+
+    #[test]
+    fn test_normalized_ladder_matches_direct_form() {
+        assert_matches_direct_form(LatticeForm::NormalizedLadder);
+    }
+
+    #[test]
+    fn test_normalized_ladder_is_passive_under_modulation() {
+        let mut lattice = LatticeAllPass::new_default(100, 10.0, 0.9, LatticeForm::NormalizedLadder);
+        lattice.set_smoothing(1.0);
+
+        // 反射係数を毎サンプル切り替えても出力エネルギーは入力エネルギーを超えない
+        let mut energy = 0.0;
+        for i in 0..4096 {
+            let k = if i % 2 == 0 { 0.95 } else { -0.95 };
+            lattice.set_reflection_coefficient(k).unwrap();
+
+            let input = if i == 0 { 1.0 } else { 0.0 };
+            let output = lattice.process(input);
+            energy += output * output;
+        }
+        assert!(energy <= 1.0 + 1e-9, "Normalized ladder should not gain energy, got {}", energy);
+    }
+
+    #[test]
+    fn test_reflection_coefficient_is_guarded() {
+        let mut lattice = LatticeAllPass::new_default(100, 10.0, 0.5, LatticeForm::NormalizedLadder);
+        lattice.set_smoothing(1.0);
+
+        // 既定のガードで|k| < 1にクランプされるため、cがNaNにならない
+        lattice.set_reflection_coefficient(1.5).unwrap();
+        for i in 0..64 {
+            let output = lattice.process(if i == 0 { 1.0 } else { 0.0 });
+            assert!(output.is_finite());
+        }
+
+        lattice.set_gain_guard(GainGuard::new(GainPolicy::Reject, 0.9));
+        assert_eq!(lattice.set_reflection_coefficient(-1.5), Err(Error::UnstableGain));
+        assert_eq!(lattice.set_gain(0.95), Err(Error::UnstableGain));
+    }
+
+    #[test]
+    fn test_reflection_coefficient_is_negated_gain() {
+        let lattice = LatticeAllPass::<f64, _>::new_default(100, 10.0, 0.7, LatticeForm::OneMultiplier);
+        assert_eq!(lattice.reflection_coefficient(), -0.7);
+    }
+}
@@ -0,0 +1,232 @@
+﻿use core::f64::consts::TAU;
+use num_traits::Float;
+use crate::error::Error;
+use crate::time::DelayTime;
+
+/// 乱数の既定のシード（0以外であれば任意）
+const DEFAULT_SEED: u32 = 0x9E37_79B9;
+
+/// LFOの波形
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LfoShape {
+    /// 正弦波
+    Sine,
+    /// 三角波
+    Triangle,
+    /// 1周期ごとに新しい乱数値へS字曲線でつなぐランダム波形
+    SmoothRandom,
+}
+
+/// `Lfo`の位相と乱数の状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LfoState<T> {
+    pub phase: T, // 位相（0以上1未満）
+    pub rng: u32, // 乱数の状態（xorshift32、0以外）
+    pub from: T,  // ランダム波形の現在の区間の始点
+    pub to: T,    // ランダム波形の現在の区間の終点
+}
+
+/// 遅延時間を揺らすための低周波オシレータ
+/// 出力は-depthからdepthの範囲のオフセット（サンプル単位）
+#[derive(Clone, Copy, Debug)]
+pub struct Lfo<T> {
+    shape: LfoShape,      // 波形
+    rate: T,              // 周波数（Hz）
+    depth: DelayTime<T>,  // 振れ幅
+    sample_rate: T,       // サンプルレート（Hz）
+    increment: T,         // 1サンプルあたりの位相の増分
+    depth_samples: T,     // 振れ幅（サンプル単位）
+    phase: T,             // 位相（0以上1未満）
+    rng: u32,             // 乱数の状態（xorshift32）
+    from: T,              // ランダム波形の現在の区間の始点
+    to: T,                // ランダム波形の現在の区間の終点
+}
+
+impl<T: Float> Lfo<T> {
+    /// 新しいLfoを作成
+    /// `shape`: 波形
+    /// `rate_hz`: 周波数（Hz）
+    /// `depth`: 振れ幅
+    /// `sample_rate`: サンプルレート（Hz）
+    pub fn new(shape: LfoShape, rate_hz: T, depth: DelayTime<T>, sample_rate: T) -> Self {
+        let mut lfo = Self {
+            shape,
+            rate: rate_hz,
+            depth,
+            sample_rate,
+            increment: T::zero(),
+            depth_samples: T::zero(),
+            phase: T::zero(),
+            rng: DEFAULT_SEED,
+            from: T::zero(),
+            to: T::zero(),
+        };
+        lfo.set_sample_rate(sample_rate);
+        lfo.reset();
+        lfo
+    }
+
+    /// 波形を設定
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+
+    /// 波形を取得
+    pub fn shape(&self) -> LfoShape {
+        self.shape
+    }
+
+    /// 周波数を設定（位相は連続したまま変わる）
+    /// `rate_hz`: 周波数（Hz）
+    pub fn set_rate(&mut self, rate_hz: T) {
+        self.rate = rate_hz;
+        self.increment = rate_hz / self.sample_rate;
+    }
+
+    /// 周波数を取得
+    pub fn rate(&self) -> T {
+        self.rate
+    }
+
+    /// 振れ幅を設定
+    /// `depth`: 振れ幅
+    pub fn set_depth(&mut self, depth: DelayTime<T>) {
+        self.depth = depth;
+        self.depth_samples = depth.to_samples(self.sample_rate);
+    }
+
+    /// 振れ幅を取得
+    pub fn depth(&self) -> DelayTime<T> {
+        self.depth
+    }
+
+    /// サンプルレートを設定（周波数と振れ幅は時間単位のまま保つ）
+    /// `sample_rate`: サンプルレート（Hz）
+    pub fn set_sample_rate(&mut self, sample_rate: T) {
+        self.sample_rate = sample_rate;
+        self.set_rate(self.rate);
+        self.set_depth(self.depth);
+    }
+
+    /// 位相と乱数の状態を初期値に戻す（同じ入力から同じ出力を再現できる）
+    pub fn reset(&mut self) {
+        self.phase = T::zero();
+        self.rng = DEFAULT_SEED;
+        self.from = T::zero();
+        self.to = self.next_random();
+    }
+
+    /// 位相と乱数の状態を保存する
+    pub fn state(&self) -> LfoState<T> {
+        LfoState {
+            phase: self.phase,
+            rng: self.rng,
+            from: self.from,
+            to: self.to,
+        }
+    }
+
+    /// 保存した位相と乱数の状態を復元する（波形、周波数、振れ幅は変えない）
+    /// `state`: `state`で保存した状態
+    /// 戻り値: 位相が0以上1未満でない、または乱数の状態が0の場合はエラー（状態は変更しない）
+    pub fn restore_state(&mut self, state: &LfoState<T>) -> Result<(), Error> {
+        if !(state.phase >= T::zero() && state.phase < T::one()) || state.rng == 0 {
+            return Err(Error::InvalidSnapshot);
+        }
+        self.phase = state.phase;
+        self.rng = state.rng;
+        self.from = state.from;
+        self.to = state.to;
+        Ok(())
+    }
+
+    /// 次のサンプルのオフセットを計算し、位相を進める
+    /// 戻り値: 遅延時間のオフセット（サンプル単位）
+    #[inline]
+    pub fn next_offset(&mut self) -> T {
+        let one = T::one();
+        let two = T::from(2.0).unwrap();
+        let value = match self.shape {
+            LfoShape::Sine => (self.phase * T::from(TAU).unwrap()).sin(),
+            LfoShape::Triangle => {
+                // 位相0で0から上昇し、正弦波と同じ向きに動く
+                let shifted = (self.phase + T::from(0.75).unwrap()).fract();
+                T::from(4.0).unwrap() * (shifted - T::from(0.5).unwrap()).abs() - one
+            }
+            LfoShape::SmoothRandom => {
+                let t = self.phase;
+                self.from + (self.to - self.from) * t * t * (T::from(3.0).unwrap() - two * t)
+            }
+        };
+
+        self.phase = self.phase + self.increment;
+        if self.phase >= one {
+            self.phase = self.phase.fract();
+            self.from = self.to;
+            self.to = self.next_random();
+        }
+
+        value * self.depth_samples
+    }
+
+    /// -1から1の一様乱数を返す
+    fn next_random(&mut self) -> T {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng = x;
+        T::from(x as f64 / u32::MAX as f64 * 2.0 - 1.0).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waveforms_stay_within_depth() {
+        for shape in [LfoShape::Sine, LfoShape::Triangle, LfoShape::SmoothRandom] {
+            let mut lfo = Lfo::new(shape, 3.0, DelayTime::Samples(4.0), 1000.0);
+            assert_eq!(lfo.next_offset(), 0.0);
+            for _ in 0..2000 {
+                assert!(lfo.next_offset().abs() <= 4.0 + 1e-12, "{shape:?} should stay within depth");
+            }
+        }
+    }
+
+    #[test]
+    fn test_triangle_follows_sine_quadrants() {
+        let mut lfo = Lfo::new(LfoShape::Triangle, 1.0, DelayTime::Samples(1.0), 8.0);
+        let values: Vec<f64> = (0..8).map(|_| lfo.next_offset()).collect();
+        assert_eq!(values, vec![0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -0.5]);
+    }
+
+    #[test]
+    fn test_reset_repeats_random_sequence() {
+        let mut lfo = Lfo::new(LfoShape::SmoothRandom, 50.0, DelayTime::Millis(1.0), 48000.0);
+        let first: Vec<f64> = (0..5000).map(|_| lfo.next_offset()).collect();
+        lfo.reset();
+        let second: Vec<f64> = (0..5000).map(|_| lfo.next_offset()).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_restore_state_continues_sequence() {
+        let mut lfo = Lfo::new(LfoShape::SmoothRandom, 50.0, DelayTime::Millis(1.0), 48000.0);
+        for _ in 0..3000 {
+            lfo.next_offset();
+        }
+        let state = lfo.state();
+        let first: Vec<f64> = (0..5000).map(|_| lfo.next_offset()).collect();
+
+        lfo.reset();
+        lfo.restore_state(&state).unwrap();
+        let second: Vec<f64> = (0..5000).map(|_| lfo.next_offset()).collect();
+        assert_eq!(first, second);
+
+        assert_eq!(lfo.restore_state(&LfoState { phase: 1.0, ..state }), Err(Error::InvalidSnapshot));
+        assert_eq!(lfo.restore_state(&LfoState { rng: 0, ..state }), Err(Error::InvalidSnapshot));
+    }
+}
@@ -1,14 +1,57 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+// `alloc`フィーチャ（`std`で有効になる）が無効な場合は、ヒープを使わない固定長の遅延線とフィルタのみ使える
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
 pub mod delay;
+pub mod error;
+pub mod event;
 pub mod allpass;
+pub mod biquad;
+pub mod first_order;
+#[cfg(feature = "alloc")]
+pub mod nested;
 pub mod interpolation;
+#[cfg(feature = "alloc")]
+pub mod lattice;
+pub mod lfo;
+#[cfg(feature = "alloc")]
+pub mod multitap;
 pub mod parameter;
+// 共有パラメータはArcとアトミック操作を使うため、それらを持たないターゲットでは使えない
+#[cfg(all(feature = "alloc", target_has_atomic = "32", target_has_atomic = "ptr"))]
+pub mod params;
+pub mod processor;
+pub mod stability;
+pub mod time;
+// C APIは共有パラメータも公開するため、同じ条件で有効にする
+#[cfg(all(feature = "alloc", target_has_atomic = "32", target_has_atomic = "ptr"))]
 pub mod capi;
 
-pub use delay::DelayLine;
-pub use allpass::AllPassFilter;
-pub use interpolation::{Interpolator, Linear, Nearest, Cubic};
-pub use parameter::SmoothedParam;
\ No newline at end of file
+pub use delay::{DelayBuffer, DelayLine, StaticDelayLine};
+#[cfg(feature = "alloc")]
+pub use delay::DelayLineSnapshot;
+pub use error::Error;
+pub use event::{ParamEvent, ParamTarget};
+pub use allpass::{AllPassFilter, StaticAllPassFilter};
+#[cfg(feature = "alloc")]
+pub use allpass::AllPassFilterSnapshot;
+pub use biquad::BiquadAllPass;
+pub use first_order::FirstOrderAllPass;
+#[cfg(feature = "alloc")]
+pub use lattice::{LatticeAllPass, LatticeForm};
+pub use lfo::{Lfo, LfoShape, LfoState};
+#[cfg(feature = "alloc")]
+pub use multitap::{MultiTapDelay, Tap};
+#[cfg(feature = "alloc")]
+pub use nested::NestedAllPass;
+pub use interpolation::{Interpolator, StatefulInterpolator, InterpolatorState, Linear, Nearest, Cubic, Lagrange, Thiran, InterpolationMode};
+#[cfg(feature = "alloc")]
+pub use interpolation::{Sinc, DynamicInterpolator};
+pub use parameter::{SmoothedParam, SmoothedParamSnapshot, SmoothingStyle, DEFAULT_SMOOTHING_TIME_MS};
+#[cfg(all(feature = "alloc", target_has_atomic = "32", target_has_atomic = "ptr"))]
+pub use params::AllPassParams;
+pub use processor::Processor;
+pub use stability::{GainGuard, GainPolicy};
+pub use time::DelayTime;
\ No newline at end of file
@@ -0,0 +1,179 @@
+﻿use num_traits::Float;
+use crate::delay::DelayLine;
+use crate::interpolation::StatefulInterpolator;
+use crate::parameter::{SmoothedParam, DEFAULT_SMOOTHING_TIME_MS};
+use crate::time::DEFAULT_SAMPLE_RATE;
+
+/// マルチタップ遅延の1つのタップ
+pub struct Tap<T, I> {
+    delay: SmoothedParam<T>, // 遅延時間（サンプル単位）
+    gain: SmoothedParam<T>,  // 出力ゲイン
+    interpolator: I,         // 補間方法
+}
+
+impl<T: Float, I> Tap<T, I> {
+    /// 新しいTapを作成
+    /// `delay`: 遅延時間（サンプル単位、1以上）
+    /// `gain`: 出力ゲイン
+    /// `interpolator`: 補間方法
+    pub fn new(delay: T, gain: T, interpolator: I) -> Self {
+        let smoothing_time = T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap();
+        let sample_rate = T::from(DEFAULT_SAMPLE_RATE).unwrap();
+
+        Self {
+            delay: SmoothedParam::with_time_constant(delay, smoothing_time, sample_rate),
+            gain: SmoothedParam::with_time_constant(gain, smoothing_time, sample_rate),
+            interpolator,
+        }
+    }
+
+    /// 現在の遅延時間を取得
+    pub fn delay(&self) -> T {
+        self.delay.current()
+    }
+
+    /// 現在の出力ゲインを取得
+    pub fn gain(&self) -> T {
+        self.gain.current()
+    }
+}
+
+/// 1つの遅延バッファを共有するN本のタップを持つ遅延
+/// 各タップは独自の遅延時間、ゲイン、補間方法を持つ
+pub struct MultiTapDelay<T, I, const N: usize> {
+    delay_line: DelayLine<T, ()>, // 共有する遅延線
+    taps: [Tap<T, I>; N],         // タップ
+}
+
+impl<T: Float, I: StatefulInterpolator<T>, const N: usize> MultiTapDelay<T, I, N> {
+    /// 新しいMultiTapDelayを作成
+    /// `max_delay_samples`: 最大遅延サンプル数
+    /// `taps`: タップ
+    pub fn new(max_delay_samples: usize, taps: [Tap<T, I>; N]) -> Self {
+        Self {
+            delay_line: DelayLine::new(max_delay_samples, ()),
+            taps,
+        }
+    }
+
+    /// 平滑化係数を設定
+    /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
+    pub fn set_smoothing(&mut self, factor: T) {
+        for tap in self.taps.iter_mut() {
+            tap.delay.set_factor(factor);
+            tap.gain.set_factor(factor);
+        }
+    }
+
+    /// 入力をプッシュし、各タップの出力をゲイン適用済みのフレームとして返す
+    /// `input`: 入力サンプル
+    /// 戻り値: タップごとの出力サンプル
+    #[inline]
+    pub fn process_frame(&mut self, input: T) -> [T; N] {
+        let mut frame = [T::zero(); N];
+        for (tap, out) in self.taps.iter_mut().zip(frame.iter_mut()) {
+            let delay = tap.delay.next();
+            let gain = tap.gain.next();
+            *out = self.delay_line.read_interpolated_with(delay, &mut tap.interpolator) * gain;
+        }
+
+        self.delay_line.push(input);
+
+        frame
+    }
+
+    /// 入力をプッシュし、全タップの出力の和を返す
+    /// `input`: 入力サンプル
+    /// 戻り値: 出力サンプル
+    #[inline]
+    pub fn process(&mut self, input: T) -> T {
+        self.process_frame(input)
+            .iter()
+            .fold(T::zero(), |sum, &sample| sum + sample)
+    }
+
+    /// ブロック単位でオーディオサンプルを処理（全タップの和）
+    /// `input`: 入力サンプルのスライス
+    /// `output`: 出力サンプルのスライス
+    pub fn process_block(&mut self, input: &[T], output: &mut [T]) {
+        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+            *out_sample = self.process(*in_sample);
+        }
+    }
+
+    /// 遅延線をゼロで埋め、各タップのパラメータと補間器を初期化する
+    pub fn reset(&mut self) {
+        self.delay_line.clear();
+        for tap in self.taps.iter_mut() {
+            tap.delay.reset();
+            tap.gain.reset();
+            tap.interpolator.reset();
+        }
+    }
+
+    /// タップの遅延時間を設定
+    /// `index`: タップ番号
+    /// `delay`: 遅延時間（サンプル単位）
+    pub fn set_tap_delay(&mut self, index: usize, delay: T) {
+        self.taps[index].delay.set_target(delay);
+    }
+
+    /// タップの出力ゲインを設定
+    /// `index`: タップ番号
+    /// `gain`: 出力ゲイン
+    pub fn set_tap_gain(&mut self, index: usize, gain: T) {
+        self.taps[index].gain.set_target(gain);
+    }
+
+    /// タップへの参照を取得
+    /// `index`: タップ番号
+    pub fn tap(&self, index: usize) -> &Tap<T, I> {
+        &self.taps[index]
+    }
+
+    /// タップの補間器への可変参照を取得
+    /// `index`: タップ番号
+    pub fn tap_interpolator_mut(&mut self, index: usize) -> &mut I {
+        &mut self.taps[index].interpolator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::{Cubic, Linear};
+
+    #[test]
+    fn test_taps_read_shared_buffer() {
+        let mut multitap = MultiTapDelay::new(64, [
+            Tap::new(3.0, 1.0, Linear),
+            Tap::new(7.0, 0.5, Linear),
+            Tap::new(12.0, -0.25, Linear),
+        ]);
+
+        let mut frames = Vec::new();
+        for i in 0..16 {
+            frames.push(multitap.process_frame(if i == 0 { 1.0 } else { 0.0 }));
+        }
+
+        // 各タップは自身の遅延位置でのみインパルスを出力する
+        for (n, frame) in frames.iter().enumerate() {
+            assert_eq!(frame[0], if n == 3 { 1.0 } else { 0.0 });
+            assert_eq!(frame[1], if n == 7 { 0.5 } else { 0.0 });
+            assert_eq!(frame[2], if n == 12 { -0.25 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn test_sum_matches_frame() {
+        let mut multitap_frame = MultiTapDelay::new(64, [Tap::new(4.5, 0.7, Cubic), Tap::new(9.25, 0.3, Cubic)]);
+        let mut multitap_sum = MultiTapDelay::new(64, [Tap::new(4.5, 0.7, Cubic), Tap::new(9.25, 0.3, Cubic)]);
+
+        for i in 0..32 {
+            let input = (i as f64 * 0.37).sin();
+            let frame = multitap_frame.process_frame(input);
+            let sum = multitap_sum.process(input);
+            assert!((frame[0] + frame[1] - sum).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+﻿use num_traits::Float;
+use crate::delay::DelayLine;
+use crate::error::Error;
+use crate::interpolation::{StatefulInterpolator, Linear};
+use crate::parameter::{SmoothedParam, DEFAULT_SMOOTHING_TIME_MS};
+use crate::processor::Processor;
+use crate::stability::GainGuard;
+use crate::time::DEFAULT_SAMPLE_RATE;
+
+/// 入れ子構造のオールパスフィルタ（Gardner / Dattorro型）
+/// 外側のオールパスの遅延要素の中に内側のプロセッサを挿入する
+/// 式: w[n] = P(v[n - D]), v[n] = x[n] + g w[n], y[n] = w[n] - g v[n]
+pub struct NestedAllPass<T, I, P> {
+    delay_line: DelayLine<T, I>,    // 遅延線
+    delay_length: SmoothedParam<T>, // 遅延時間（サンプル単位）
+    g: SmoothedParam<T>,            // フィードバックゲイン
+    gain_guard: GainGuard<T>,       // ゲインの安定化ガード
+    inner: P,                       // 遅延要素内のプロセッサ
+}
+
+impl<T: Float, P: Processor<T>> NestedAllPass<T, Linear, P> {
+    pub fn new_default(max_delay_samples: usize, initial_delay: T, gain: T, inner: P) -> Self {
+        Self::new(max_delay_samples, initial_delay, gain, Linear, inner)
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>, P: Processor<T>> NestedAllPass<T, I, P> {
+    /// 新しいNestedAllPassを作成
+    /// `max_delay_samples`: 最大遅延サンプル数
+    /// `initial_delay`: 初期遅延時間（サンプル単位）
+    /// `gain`: フィードバックゲイン（安定範囲にクランプされ、NaNは0になる）
+    /// `interpolator`: 補間方法
+    /// `inner`: 遅延要素内のプロセッサ
+    pub fn new(max_delay_samples: usize, initial_delay: T, gain: T, interpolator: I, inner: P) -> Self {
+        let smoothing_time = T::from(DEFAULT_SMOOTHING_TIME_MS).unwrap();
+        let sample_rate = T::from(DEFAULT_SAMPLE_RATE).unwrap();
+        let gain_guard = GainGuard::default();
+        let gain = gain_guard.apply(gain).unwrap_or(T::zero());
+
+        Self {
+            delay_line: DelayLine::new(max_delay_samples, interpolator),
+            delay_length: SmoothedParam::with_time_constant(initial_delay, smoothing_time, sample_rate),
+            g: SmoothedParam::with_time_constant(gain, smoothing_time, sample_rate),
+            gain_guard,
+            inner,
+        }
+    }
+
+    /// 平滑化係数を設定
+    /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
+    pub fn set_smoothing(&mut self, factor: T) {
+        self.delay_length.set_factor(factor);
+        self.g.set_factor(factor);
+    }
+
+    /// オーディオサンプルを処理
+    /// `input`: 入力サンプル
+    /// 戻り値: 出力サンプル
+    #[inline]
+    pub fn process(&mut self, input: T) -> T {
+        let current_delay = self.delay_length.next();
+        let current_g = self.g.next();
+
+        // 遅延線の出力を内側のプロセッサに通す
+        let delayed_value = self.delay_line.read_interpolated_mut(current_delay);
+        let inner_value = self.inner.process(delayed_value);
+
+        let v_n = input + (current_g * inner_value);
+        let output = inner_value - (current_g * v_n);
+
+        self.delay_line.push(v_n);
+
+        output
+    }
+
+    /// ブロック単位でオーディオサンプルを処理
+    /// `input`: 入力サンプルのスライス
+    /// `output`: 出力サンプルのスライス
+    pub fn process_block(&mut self, input: &[T], output: &mut [T]) {
+        for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+            *out_sample = self.process(*in_sample);
+        }
+    }
+
+    /// ブロック単位でオーディオサンプルをインプレース処理
+    /// `buffer`: 入出力サンプルのスライス
+    pub fn process_block_inplace(&mut self, buffer: &mut [T]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+
+    /// 遅延線と内側のプロセッサを初期化し、パラメータを目標値に合わせる
+    pub fn reset(&mut self) {
+        self.delay_line.reset();
+        self.inner.reset();
+        self.delay_length.reset();
+        self.g.reset();
+    }
+
+    /// 遅延時間を設定
+    /// `delay`: 遅延時間（サンプル単位）
+    pub fn set_delay(&mut self, delay: T) {
+        self.delay_length.set_target(delay);
+    }
+
+    /// フィードバックゲインを設定
+    /// ガードのポリシーに従って安定範囲に収めた値を目標値にする
+    /// `gain`: フィードバックゲイン
+    /// 戻り値: Rejectで範囲外の場合はエラー（目標値は変更しない）
+    pub fn set_gain(&mut self, gain: T) -> Result<(), Error> {
+        let gain = self.gain_guard.apply(gain)?;
+        self.g.set_target(gain);
+        Ok(())
+    }
+
+    /// ゲインの安定化ガードを設定
+    /// `gain_guard`: 以降の`set_gain`に適用するガード
+    pub fn set_gain_guard(&mut self, gain_guard: GainGuard<T>) {
+        self.gain_guard = gain_guard;
+    }
+
+    /// ゲインの安定化ガードを取得
+    pub fn gain_guard(&self) -> GainGuard<T> {
+        self.gain_guard
+    }
+
+    /// 内側のプロセッサへの参照を取得
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// 内側のプロセッサへの可変参照を取得
+    pub fn inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+}
+
+impl<T: Float, I: StatefulInterpolator<T>, P: Processor<T>> Processor<T> for NestedAllPass<T, I, P> {
+    #[inline]
+    fn process(&mut self, input: T) -> T {
+        NestedAllPass::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        NestedAllPass::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allpass::AllPassFilter;
+
+    #[test]
+    fn test_nested_impulse_response_energy_is_unity() {
+        let inner = AllPassFilter::new_default(100, 7.0, 0.4);
+        let middle = NestedAllPass::new_default(100, 13.0, 0.5, inner);
+        let mut nested = NestedAllPass::new_default(100, 29.0, 0.6, middle);
+
+        let mut impulse_response = vec![0.0; 20000];
+        impulse_response[0] = 1.0;
+        nested.process_block_inplace(&mut impulse_response);
+
+        // オールパスのインパルス応答のエネルギーは1になる
+        let energy: f64 = impulse_response.iter().map(|h| h * h).sum();
+        assert!((energy - 1.0).abs() < 1e-6, "Energy of nested allpass should be 1.0, got {}", energy);
+    }
+}
@@ -1,4 +1,34 @@
 ﻿use num_traits::Float;
+use crate::time::DelayTime;
+
+/// 既定の平滑化時定数（ミリ秒）
+/// 44.1 kHzで平滑化係数0.01とほぼ同じ応答になる
+pub const DEFAULT_SMOOTHING_TIME_MS: f64 = 2.25;
+
+/// 目標値への近づき方
+/// ランプ系の長さは目標値を設定してから到達するまでの時間
+/// サンプル数以外で指定した長さはサンプルレートに合わせてサンプル数へ換算する
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmoothingStyle<T> {
+    /// 1次の指数平滑化（平滑化係数で速さが決まる）
+    Exponential,
+    /// 一定の傾きで直線的に変化
+    Linear(DelayTime<T>),
+    /// 一定の比率で変化（ゲインや周波数向け。符号が異なる、または0を含む場合は直線）
+    Logarithmic(DelayTime<T>),
+    /// 始点と終点で傾きが0になるS字曲線（smoothstep）で変化
+    SCurve(DelayTime<T>),
+}
+
+/// `SmoothedParam`の平滑化の進行状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmoothedParamSnapshot<T> {
+    pub current: T,      // 現在値
+    pub target: T,       // 目標値
+    pub start: T,        // ランプの始点
+    pub ramp_pos: usize, // ランプの進行位置（サンプル単位）
+}
 
 /// 平滑化機能を持つパラメータ構造体
 #[derive(Clone, Copy, Debug)]
@@ -6,6 +36,10 @@ pub struct SmoothedParam<T> {
     current_value: T,
     target_value: T,
     factor: T,
+    style: SmoothingStyle<T>, // 目標値への近づき方
+    ramp_len: usize,          // ランプの長さ（サンプル単位）
+    start_value: T,           // ランプの始点
+    ramp_pos: usize,          // ランプの進行位置
 }
 
 impl<T: Float> SmoothedParam<T> {
@@ -17,11 +51,41 @@ impl<T: Float> SmoothedParam<T> {
             current_value: initial_value,
             target_value: initial_value,
             factor: smooth_factor,
+            style: SmoothingStyle::Exponential,
+            ramp_len: 0,
+            start_value: initial_value,
+            ramp_pos: 0,
+        }
+    }
+
+    /// 時定数を指定して新しいSmoothedParamを作成
+    /// サンプルレートが変わっても同じ時間で目標値に近づく
+    /// `initial_value`: 初期値
+    /// `time_constant_ms`: 目標値との差が1/eになるまでの時間（ミリ秒）
+    /// `sample_rate`: サンプルレート（Hz）
+    pub fn with_time_constant(initial_value: T, time_constant_ms: T, sample_rate: T) -> Self {
+        Self::new(initial_value, Self::factor_for(time_constant_ms, sample_rate))
+    }
+
+    /// 時定数とサンプルレートから平滑化係数を計算
+    /// `time_constant_ms`: 目標値との差が1/eになるまでの時間（ミリ秒、0以下で即時変化）
+    /// `sample_rate`: サンプルレート（Hz）
+    /// 戻り値: 平滑化係数 1 - exp(-1 / (τ fs))
+    pub fn factor_for(time_constant_ms: T, sample_rate: T) -> T {
+        let samples = time_constant_ms * sample_rate / T::from(1000.0).unwrap();
+        if samples <= T::zero() {
+            return T::one();
         }
+        T::one() - (-samples.recip()).exp()
     }
 
     /// 目標値を設定
+    /// ランプ系のスタイルでは現在値から新しいランプを始める
     pub fn set_target(&mut self, target: T) {
+        if target != self.target_value {
+            self.start_value = self.current_value;
+            self.ramp_pos = 0;
+        }
         self.target_value = target;
     }
 
@@ -29,6 +93,74 @@ impl<T: Float> SmoothedParam<T> {
     pub fn set_immediate(&mut self, value: T) {
         self.current_value = value;
         self.target_value = value;
+        self.start_value = value;
+    }
+
+    /// 目標値への近づき方を設定
+    /// `style`: 平滑化のスタイル
+    /// `sample_rate`: ランプの長さの換算に使うサンプルレート（Hz）
+    pub fn set_style(&mut self, style: SmoothingStyle<T>, sample_rate: T) {
+        self.style = style;
+        self.ramp_len = Self::ramp_len_for(style, sample_rate);
+        self.start_value = self.current_value;
+        self.ramp_pos = 0;
+    }
+
+    /// 目標値への近づき方を取得
+    pub fn style(&self) -> SmoothingStyle<T> {
+        self.style
+    }
+
+    /// サンプルレートに合わせてランプの長さを再計算する（進行中のランプは同じ割合の位置から続ける）
+    /// `sample_rate`: サンプルレート（Hz）
+    pub fn set_ramp_sample_rate(&mut self, sample_rate: T) {
+        let ramp_len = Self::ramp_len_for(self.style, sample_rate);
+        if let Some(ramp_pos) = (self.ramp_pos * ramp_len).checked_div(self.ramp_len) {
+            self.ramp_pos = ramp_pos;
+        }
+        self.ramp_len = ramp_len;
+    }
+
+    /// ランプの長さをサンプル数に換算（1サンプル未満は1サンプル）
+    fn ramp_len_for(style: SmoothingStyle<T>, sample_rate: T) -> usize {
+        match style {
+            SmoothingStyle::Exponential => 0,
+            SmoothingStyle::Linear(time) | SmoothingStyle::Logarithmic(time) | SmoothingStyle::SCurve(time) => {
+                time.to_samples(sample_rate).round().to_usize().unwrap_or(0).max(1)
+            }
+        }
+    }
+
+    /// 時定数で平滑化係数を設定
+    /// `time_constant_ms`: 目標値との差が1/eになるまでの時間（ミリ秒）
+    /// `sample_rate`: サンプルレート（Hz）
+    pub fn set_time_constant(&mut self, time_constant_ms: T, sample_rate: T) {
+        self.factor = Self::factor_for(time_constant_ms, sample_rate);
+    }
+
+    /// 現在値を目標値に合わせ、平滑化を終了する
+    pub fn reset(&mut self) {
+        self.current_value = self.target_value;
+        self.start_value = self.target_value;
+    }
+
+    /// 現在値、目標値とランプの進行状態を保存する
+    pub fn snapshot(&self) -> SmoothedParamSnapshot<T> {
+        SmoothedParamSnapshot {
+            current: self.current_value,
+            target: self.target_value,
+            start: self.start_value,
+            ramp_pos: self.ramp_pos,
+        }
+    }
+
+    /// 保存した進行状態を復元する（平滑化係数とスタイルは変えない）
+    /// `snapshot`: `snapshot`で保存した状態
+    pub fn restore(&mut self, snapshot: &SmoothedParamSnapshot<T>) {
+        self.current_value = snapshot.current;
+        self.target_value = snapshot.target;
+        self.start_value = snapshot.start;
+        self.ramp_pos = snapshot.ramp_pos;
     }
 
     /// 平滑化係数を設定
@@ -39,22 +171,181 @@ impl<T: Float> SmoothedParam<T> {
     /// 次の平滑化ステップを計算し、現在値を更新して返す
     #[inline]
     pub fn next(&mut self) -> T {
-        if self.current_value != self.target_value {
-            let diff = self.target_value - self.current_value;
-
-            // 十分に近い場合は目標値に直接設定
-            if diff.abs() < T::epsilon() {
-                self.current_value = self.target_value;
-            } else {
-                self.current_value = self.current_value + (diff * self.factor);
+        if self.current_value == self.target_value {
+            return self.current_value;
+        }
+
+        match self.style {
+            SmoothingStyle::Exponential => {
+                let diff = self.target_value - self.current_value;
+                let next = self.current_value + (diff * self.factor);
+
+                // 十分に近い場合、または丸めで値が進まなくなった場合は目標値に直接設定
+                if diff.abs() < T::epsilon() || next == self.current_value {
+                    self.current_value = self.target_value;
+                } else {
+                    self.current_value = next;
+                }
+                return self.current_value;
             }
+            SmoothingStyle::Linear(_) | SmoothingStyle::Logarithmic(_) | SmoothingStyle::SCurve(_) => {}
         }
 
+        self.ramp_pos += 1;
+        if self.ramp_pos >= self.ramp_len {
+            self.current_value = self.target_value;
+            return self.current_value;
+        }
+
+        let t = T::from(self.ramp_pos).unwrap() / T::from(self.ramp_len).unwrap();
+        let (start, target) = (self.start_value, self.target_value);
+        self.current_value = match self.style {
+            SmoothingStyle::Logarithmic(_) if start * target > T::zero() => start * (target / start).powf(t),
+            SmoothingStyle::SCurve(_) => {
+                let three = T::from(3.0).unwrap();
+                let two = T::from(2.0).unwrap();
+                start + (target - start) * t * t * (three - two * t)
+            }
+            _ => start + (target - start) * t,
+        };
+
         self.current_value
     }
 
+    /// 1ブロック分の平滑化された値を書き込む
+    /// 平滑化が終わっている場合は現在値で埋めるだけで済ませる
+    /// `output`: 出力先（各サンプルの`next()`の値）
+    pub fn next_block(&mut self, output: &mut [T]) {
+        if !self.is_smoothing() {
+            output.fill(self.current_value);
+            return;
+        }
+
+        for value in output.iter_mut() {
+            *value = self.next();
+        }
+    }
+
+    /// 目標値に向かって変化している途中かどうか
+    pub fn is_smoothing(&self) -> bool {
+        self.current_value != self.target_value
+    }
+
+    /// 目標値を取得
+    pub fn target(&self) -> T {
+        self.target_value
+    }
+
+    /// 平滑化係数を取得
+    pub fn factor(&self) -> T {
+        self.factor
+    }
+
     /// 現在の値を取得
     pub fn current(&self) -> T {
         self.current_value
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 目標値を設定してから`len`サンプル分の値を集める
+    fn ramp(style: SmoothingStyle<f64>, from: f64, to: f64, len: usize) -> Vec<f64> {
+        let mut param = SmoothedParam::new(from, 0.01);
+        param.set_style(style, 48000.0);
+        param.set_target(to);
+        (0..len).map(|_| param.next()).collect()
+    }
+
+    #[test]
+    fn test_linear_ramp_has_constant_slope() {
+        let values = ramp(SmoothingStyle::Linear(DelayTime::Samples(4.0)), 0.0, 1.0, 6);
+        assert_eq!(values, vec![0.25, 0.5, 0.75, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_logarithmic_ramp_has_constant_ratio() {
+        let values = ramp(SmoothingStyle::Logarithmic(DelayTime::Samples(3.0)), 1.0, 8.0, 3);
+        for (value, expected) in values.iter().zip([2.0, 4.0, 8.0]) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+
+        // 0を含む場合は直線になる
+        assert_eq!(ramp(SmoothingStyle::Logarithmic(DelayTime::Samples(2.0)), 0.0, 1.0, 2), vec![0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_s_curve_is_symmetric() {
+        let values = ramp(SmoothingStyle::SCurve(DelayTime::Samples(4.0)), 0.0, 1.0, 4);
+        assert!(values[0] < 0.25);
+        assert_eq!(values[1], 0.5);
+        assert!((values[0] + values[2] - 1.0).abs() < 1e-12);
+        assert_eq!(values[3], 1.0);
+    }
+
+    #[test]
+    fn test_ramp_time_follows_sample_rate() {
+        // 1ミリ秒は8 kHzで8サンプル、16 kHzで16サンプル
+        let mut param = SmoothedParam::new(0.0, 0.01);
+        param.set_style(SmoothingStyle::Linear(DelayTime::Millis(1.0)), 8000.0);
+        param.set_target(1.0);
+        assert_eq!(param.next(), 0.125);
+
+        // 進行中のランプは同じ割合の位置から続ける
+        param.set_ramp_sample_rate(16000.0);
+        assert_eq!(param.next(), 0.1875);
+        let rest: Vec<f64> = (0..13).map(|_| param.next()).collect();
+        assert_eq!(rest[12], 1.0);
+        assert!(rest[11] < 1.0);
+    }
+
+    #[test]
+    fn test_retarget_restarts_from_current_value() {
+        let mut param = SmoothedParam::new(0.0, 0.01);
+        param.set_style(SmoothingStyle::Linear(DelayTime::Samples(4.0)), 48000.0);
+        param.set_target(1.0);
+        param.next();
+        param.next();
+
+        // 同じ目標値の再設定ではランプを始め直さない
+        param.set_target(1.0);
+        assert_eq!(param.next(), 0.75);
+
+        param.set_target(0.0);
+        assert_eq!(param.next(), 0.5625);
+    }
+
+    #[test]
+    fn test_exponential_reaches_large_targets() {
+        // 目標値が大きいと差がepsilonより小さくなる前に丸めで止まるため、止まった時点で目標値にする
+        let mut param = SmoothedParam::new(0.0, 0.01);
+        param.set_target(75.3);
+        for _ in 0..100_000 {
+            param.next();
+        }
+        assert!(!param.is_smoothing());
+        assert_eq!(param.current(), 75.3);
+    }
+
+    #[test]
+    fn test_next_block_matches_next() {
+        let mut param_block = SmoothedParam::new(0.0, 0.1);
+        let mut param_single = SmoothedParam::new(0.0, 0.1);
+        param_block.set_target(1.0);
+        param_single.set_target(1.0);
+        assert!(param_block.is_smoothing());
+
+        let mut block = [0.0; 32];
+        param_block.next_block(&mut block);
+        for value in block {
+            assert_eq!(value, param_single.next());
+        }
+
+        param_block.reset();
+        assert!(!param_block.is_smoothing());
+        param_block.next_block(&mut block);
+        assert!(block.iter().all(|&value| value == 1.0));
+    }
+}
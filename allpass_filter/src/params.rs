@@ -0,0 +1,101 @@
+﻿use num_traits::Float;
+use alloc::sync::Arc;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// 未設定を表す値（f32のNaN）
+const UNSET: u32 = 0x7fc0_0000;
+
+/// スレッド間で共有する目標値
+/// 64ビットのアトミック操作を持たない32ビットのターゲットでも使えるようにf32で保持する
+struct Shared {
+    delay: AtomicU32,     // 遅延時間（サンプル単位）
+    gain: AtomicU32,      // フィードバックゲイン
+    smoothing: AtomicU32, // 平滑化係数
+}
+
+/// ロックなしで別スレッドから書き換えられるAllPassFilterのパラメータ
+/// UIスレッドが`set_*`で書き込み、オーディオスレッドがブロックごとに
+/// `AllPassFilter::apply_params`で最新の目標値を読み込む
+/// 複製したハンドルはすべて同じ値を共有する
+/// 値はf32の精度で受け渡す
+pub struct AllPassParams<T> {
+    shared: Arc<Shared>,
+    _sample: PhantomData<fn() -> T>,
+}
+
+impl<T: Float> AllPassParams<T> {
+    /// すべて未設定のAllPassParamsを作成
+    /// 未設定のパラメータは`apply_params`で変更されない
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                delay: AtomicU32::new(UNSET),
+                gain: AtomicU32::new(UNSET),
+                smoothing: AtomicU32::new(UNSET),
+            }),
+            _sample: PhantomData,
+        }
+    }
+
+    /// 遅延時間の目標値を設定
+    /// `delay`: 遅延時間（サンプル単位）
+    pub fn set_delay(&self, delay: T) {
+        store(&self.shared.delay, delay);
+    }
+
+    /// フィードバックゲインの目標値を設定
+    /// `gain`: フィードバックゲイン（適用時にフィルタのゲインガードを通す）
+    pub fn set_gain(&self, gain: T) {
+        store(&self.shared.gain, gain);
+    }
+
+    /// 平滑化係数を設定
+    /// `factor`: 平滑化係数 (1.0で即時変化、0.0に近づくほど遅く変化)
+    pub fn set_smoothing(&self, factor: T) {
+        store(&self.shared.smoothing, factor);
+    }
+
+    /// 最新の遅延時間（未設定の場合はNone）
+    pub fn delay(&self) -> Option<T> {
+        load(&self.shared.delay)
+    }
+
+    /// 最新のフィードバックゲイン（未設定の場合はNone）
+    pub fn gain(&self) -> Option<T> {
+        load(&self.shared.gain)
+    }
+
+    /// 最新の平滑化係数（未設定の場合はNone）
+    pub fn smoothing(&self) -> Option<T> {
+        load(&self.shared.smoothing)
+    }
+}
+
+impl<T: Float> Default for AllPassParams<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for AllPassParams<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+            _sample: PhantomData,
+        }
+    }
+}
+
+/// 値をf32のビット列として書き込む
+#[inline]
+fn store<T: Float>(slot: &AtomicU32, value: T) {
+    slot.store(value.to_f32().unwrap_or(f32::NAN).to_bits(), Ordering::Relaxed);
+}
+
+/// f32のビット列から値を読み出す（NaNは未設定として扱う）
+#[inline]
+fn load<T: Float>(slot: &AtomicU32) -> Option<T> {
+    let value = f32::from_bits(slot.load(Ordering::Relaxed));
+    if value.is_nan() { None } else { T::from(value) }
+}
@@ -0,0 +1,11 @@
+﻿/// 1サンプルずつ処理するプロセッサのトレイト
+/// `NestedAllPass`の遅延要素内に入れるプロセッサはこれを実装する
+pub trait Processor<T> {
+    /// オーディオサンプルを処理
+    /// `input`: 入力サンプル
+    /// 戻り値: 出力サンプル
+    fn process(&mut self, input: T) -> T;
+
+    /// 内部状態を初期化する
+    fn reset(&mut self);
+}
@@ -0,0 +1,82 @@
+﻿use num_traits::Float;
+use crate::error::Error;
+
+/// 安定範囲として扱う|g|の上限の既定値
+pub const DEFAULT_MAX_GAIN: f64 = 0.999;
+
+/// 安定範囲外のゲインに対する扱い
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GainPolicy {
+    /// [-max_gain, max_gain]にクランプする
+    Clamp,
+    /// 範囲外の値をエラーとして拒否する
+    Reject,
+    /// max_gain * tanh(g / max_gain)で滑らかに制限する（範囲内の値も少し小さくなる）
+    SoftLimit,
+}
+
+/// フィードバックゲインを安定範囲に収めるガード
+#[derive(Clone, Copy, Debug)]
+pub struct GainGuard<T> {
+    policy: GainPolicy, // 範囲外の値の扱い
+    max_gain: T,        // |g|の上限
+}
+
+impl<T: Float> GainGuard<T> {
+    /// 新しいGainGuardを作成
+    /// `policy`: 範囲外の値の扱い
+    /// `max_gain`: |g|の上限 (0 < max_gain < 1、範囲外の場合はパニック)
+    pub fn new(policy: GainPolicy, max_gain: T) -> Self {
+        assert!(max_gain > T::zero() && max_gain < T::one(), "max_gain must be in (0, 1)");
+        Self { policy, max_gain }
+    }
+
+    /// 上限を検証してGainGuardを作成
+    /// `policy`: 範囲外の値の扱い
+    /// `max_gain`: |g|の上限 (0 < max_gain < 1)
+    /// 戻り値: 生成したGainGuard（上限が範囲外、またはNaNの場合はエラー）
+    pub fn try_new(policy: GainPolicy, max_gain: T) -> Result<Self, Error> {
+        if !(max_gain > T::zero() && max_gain < T::one()) {
+            return Err(Error::UnstableGain);
+        }
+        Ok(Self { policy, max_gain })
+    }
+
+    /// ポリシーを適用したゲインを返す
+    /// `gain`: フィードバックゲイン
+    /// 戻り値: 安定範囲に収めたゲイン（Rejectで範囲外、またはNaNの場合はエラー）
+    pub fn apply(&self, gain: T) -> Result<T, Error> {
+        if gain.is_nan() {
+            return Err(Error::UnstableGain);
+        }
+
+        match self.policy {
+            GainPolicy::Clamp => Ok(gain.max(-self.max_gain).min(self.max_gain)),
+            GainPolicy::Reject => {
+                if gain.abs() <= self.max_gain {
+                    Ok(gain)
+                } else {
+                    Err(Error::UnstableGain)
+                }
+            }
+            GainPolicy::SoftLimit => Ok(self.max_gain * (gain / self.max_gain).tanh()),
+        }
+    }
+
+    /// ポリシーを取得
+    pub fn policy(&self) -> GainPolicy {
+        self.policy
+    }
+
+    /// |g|の上限を取得
+    pub fn max_gain(&self) -> T {
+        self.max_gain
+    }
+}
+
+impl<T: Float> Default for GainGuard<T> {
+    /// Clamp、上限`DEFAULT_MAX_GAIN`のGainGuardを作成
+    fn default() -> Self {
+        Self::new(GainPolicy::Clamp, T::from(DEFAULT_MAX_GAIN).unwrap())
+    }
+}
@@ -0,0 +1,45 @@
+﻿use num_traits::Float;
+
+/// サンプルレートが未設定の場合に使うサンプルレート
+pub const DEFAULT_SAMPLE_RATE: f64 = 44100.0;
+
+/// 単位付きの遅延時間
+/// サンプルレートを与えるとサンプル数に変換できる
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DelayTime<T> {
+    /// サンプル数（サンプルレートに依存しない）
+    Samples(T),
+    /// ミリ秒
+    Millis(T),
+    /// 秒
+    Seconds(T),
+    /// テンポ同期（`bpm`のテンポで`beats`拍分）
+    Tempo { bpm: T, beats: T },
+}
+
+impl<T: Float> DelayTime<T> {
+    /// サンプル数に変換
+    /// `sample_rate`: サンプルレート（Hz）
+    /// 戻り値: 遅延時間（サンプル単位）
+    pub fn to_samples(self, sample_rate: T) -> T {
+        match self {
+            DelayTime::Samples(samples) => samples,
+            DelayTime::Millis(ms) => sample_rate * ms / T::from(1000.0).unwrap(),
+            DelayTime::Seconds(seconds) => sample_rate * seconds,
+            DelayTime::Tempo { bpm, beats } => sample_rate * beats * T::from(60.0).unwrap() / bpm,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_samples() {
+        assert_eq!(DelayTime::Samples(12.5).to_samples(48000.0), 12.5);
+        assert_eq!(DelayTime::Millis(10.0).to_samples(48000.0), 480.0);
+        assert_eq!(DelayTime::Seconds(0.5).to_samples(44100.0), 22050.0);
+        assert_eq!(DelayTime::Tempo { bpm: 120.0, beats: 0.25 }.to_samples(48000.0), 6000.0);
+    }
+}
@@ -1,19 +1,88 @@
 ﻿#pragma once
 #include <cstddef>
+#include <cstdint>
 #include <vector>
 
 extern "C" {
     struct CAllPass;
+    struct CAllPassParams;
 
     CAllPass* allpass_create(size_t max_delay, float initial_delay, float gain);
+    CAllPass* allpass_create_with_interpolation(size_t max_delay, float initial_delay, float gain, uint32_t mode);
     void allpass_destroy(CAllPass* ptr);
     float allpass_process(CAllPass* ptr, float input);
     void allpass_process_block(CAllPass* ptr, const float* input, float* output, size_t len);
     void allpass_set_delay(CAllPass* ptr, float delay);
-    void allpass_set_gain(CAllPass* ptr, float gain);
+    bool allpass_set_gain(CAllPass* ptr, float gain);
+    bool allpass_set_gain_policy(CAllPass* ptr, uint32_t policy, float max_gain);
     void allpass_set_smoothing(CAllPass* ptr, float factor);
+    void allpass_reset(CAllPass* ptr);
+    size_t allpass_save_state(const CAllPass* ptr, uint8_t* out, size_t capacity);
+    bool allpass_load_state(CAllPass* ptr, const uint8_t* data, size_t len);
+    CAllPassParams* allpass_params_create();
+    void allpass_params_destroy(CAllPassParams* params);
+    void allpass_params_set_delay(const CAllPassParams* params, float delay);
+    void allpass_params_set_gain(const CAllPassParams* params, float gain);
+    void allpass_params_set_smoothing(const CAllPassParams* params, float factor);
+    bool allpass_apply_params(CAllPass* ptr, const CAllPassParams* params);
+    bool allpass_set_interpolation(CAllPass* ptr, uint32_t mode);
 }
 
+enum class GainPolicy : uint32_t {
+    Clamp = 0,
+    Reject = 1,
+    SoftLimit = 2,
+};
+
+enum class InterpolationMode : uint32_t {
+    Nearest = 0,
+    Linear = 1,
+    Cubic = 2,
+    Sinc = 3,
+    Thiran = 4,
+};
+
+// UIスレッドとオーディオスレッドで共有できるパラメータ（set_*はロックなしでどのスレッドからでも呼べる）
+class AllPassParams {
+private:
+    CAllPassParams* handle;
+
+public:
+    AllPassParams() {
+        handle = allpass_params_create();
+    }
+
+    ~AllPassParams() {
+        if (handle) {
+            allpass_params_destroy(handle);
+            handle = nullptr;
+        }
+    }
+
+    AllPassParams(const AllPassParams&) = delete;
+    AllPassParams& operator=(const AllPassParams&) = delete;
+
+    AllPassParams(AllPassParams&& other) noexcept : handle(other.handle) {
+        other.handle = nullptr;
+    }
+
+    void set_delay(float delay) const {
+        allpass_params_set_delay(handle, delay);
+    }
+
+    void set_gain(float gain) const {
+        allpass_params_set_gain(handle, gain);
+    }
+
+    void set_smoothing(float factor) const {
+        allpass_params_set_smoothing(handle, factor);
+    }
+
+    const CAllPassParams* native_handle() const {
+        return handle;
+    }
+};
+
 class AllPassFilter {
 private:
     CAllPass* handle;
@@ -23,6 +92,10 @@ public:
         handle = allpass_create(max_delay, initial_delay, gain);
     }
 
+    AllPassFilter(size_t max_delay, float initial_delay, float gain, InterpolationMode mode) {
+        handle = allpass_create_with_interpolation(max_delay, initial_delay, gain, static_cast<uint32_t>(mode));
+    }
+
     ~AllPassFilter() {
         if (handle) {
             allpass_destroy(handle);
@@ -53,11 +126,38 @@ public:
         allpass_set_delay(handle, delay);
     }
 
-    void set_gain(float gain) {
-        allpass_set_gain(handle, gain);
+    bool set_gain(float gain) {
+        return allpass_set_gain(handle, gain);
+    }
+
+    bool set_gain_policy(GainPolicy policy, float max_gain) {
+        return allpass_set_gain_policy(handle, static_cast<uint32_t>(policy), max_gain);
     }
 
     void set_smoothing(float factor) {
         allpass_set_smoothing(handle, factor);
     }
+
+    void reset() {
+        allpass_reset(handle);
+    }
+
+    std::vector<uint8_t> save_state() const {
+        std::vector<uint8_t> state(allpass_save_state(handle, nullptr, 0));
+        allpass_save_state(handle, state.data(), state.size());
+        return state;
+    }
+
+    bool load_state(const std::vector<uint8_t>& state) {
+        return allpass_load_state(handle, state.data(), state.size());
+    }
+
+    // オーディオスレッドでブロックごとに呼ぶ
+    bool apply_params(const AllPassParams& params) {
+        return allpass_apply_params(handle, params.native_handle());
+    }
+
+    bool set_interpolation(InterpolationMode mode) {
+        return allpass_set_interpolation(handle, static_cast<uint32_t>(mode));
+    }
 };
\ No newline at end of file
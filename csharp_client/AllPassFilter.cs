@@ -3,6 +3,28 @@ using System.Runtime.InteropServices;
 
 namespace CSharpClient
 {
+    /// <summary>
+    /// 補間方法
+    /// </summary>
+    public enum InterpolationMode : uint
+    {
+        Nearest = 0,
+        Linear = 1,
+        Cubic = 2,
+        Sinc = 3,
+        Thiran = 4,
+    }
+
+    /// <summary>
+    /// 安定範囲外のゲインに対する扱い
+    /// </summary>
+    public enum GainPolicy : uint
+    {
+        Clamp = 0,
+        Reject = 1,
+        SoftLimit = 2,
+    }
+
     public class AllPassFilter : IDisposable
     {
         /// <summary>
@@ -15,6 +37,9 @@ namespace CSharpClient
             [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
             public static extern IntPtr allpass_create(UIntPtr max_delay, float initial_delay, float gain);
 
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern IntPtr allpass_create_with_interpolation(UIntPtr max_delay, float initial_delay, float gain, uint mode);
+
             [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
             public static extern void allpass_destroy(IntPtr ptr);
 
@@ -28,10 +53,33 @@ namespace CSharpClient
             public static extern void allpass_set_delay(IntPtr ptr, float delay);
 
             [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-            public static extern void allpass_set_gain(IntPtr ptr, float gain);
+            [return: MarshalAs(UnmanagedType.U1)]
+            public static extern bool allpass_set_gain(IntPtr ptr, float gain);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            [return: MarshalAs(UnmanagedType.U1)]
+            public static extern bool allpass_set_gain_policy(IntPtr ptr, uint policy, float max_gain);
 
             [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
             public static extern void allpass_set_smoothing(IntPtr ptr, float factor);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern void allpass_reset(IntPtr ptr);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern UIntPtr allpass_save_state(IntPtr ptr, byte[] output, UIntPtr capacity);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            [return: MarshalAs(UnmanagedType.U1)]
+            public static extern bool allpass_load_state(IntPtr ptr, byte[] data, UIntPtr len);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            [return: MarshalAs(UnmanagedType.U1)]
+            public static extern bool allpass_set_interpolation(IntPtr ptr, uint mode);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            [return: MarshalAs(UnmanagedType.U1)]
+            public static extern bool allpass_apply_params(IntPtr ptr, IntPtr parameters);
         }
 
         private IntPtr _handle;         // ネイティブオブジェクトのハンドル
@@ -45,6 +93,14 @@ namespace CSharpClient
             _handle = NativeMethods.allpass_create((UIntPtr)max_delay, initial_delay, gain);
         }
 
+        /// <summary>
+        /// 補間方法を指定するコンストラクタ
+        /// </summary>
+        public AllPassFilter(int max_delay, float initial_delay, float gain, InterpolationMode mode)
+        {
+            _handle = NativeMethods.allpass_create_with_interpolation((UIntPtr)max_delay, initial_delay, gain, (uint)mode);
+        }
+
         /// <summary>
         /// デストラクタ
         /// </summary>
@@ -99,10 +155,16 @@ namespace CSharpClient
             NativeMethods.allpass_set_delay(_handle, delay);
         }
 
-        public void SetGain(float gain)
+        public bool SetGain(float gain)
         {
             CheckDisposed();
-            NativeMethods.allpass_set_gain(_handle, gain);
+            return NativeMethods.allpass_set_gain(_handle, gain);
+        }
+
+        public bool SetGainPolicy(GainPolicy policy, float maxGain)
+        {
+            CheckDisposed();
+            return NativeMethods.allpass_set_gain_policy(_handle, (uint)policy, maxGain);
         }
 
         public void SetSmoothing(float factor)
@@ -111,9 +173,137 @@ namespace CSharpClient
             NativeMethods.allpass_set_smoothing(_handle, factor);
         }
 
+        public void Reset()
+        {
+            CheckDisposed();
+            NativeMethods.allpass_reset(_handle);
+        }
+
+        public byte[] SaveState()
+        {
+            CheckDisposed();
+            var size = NativeMethods.allpass_save_state(_handle, null, UIntPtr.Zero);
+            var state = new byte[(int)size];
+            NativeMethods.allpass_save_state(_handle, state, size);
+            return state;
+        }
+
+        public bool LoadState(byte[] state)
+        {
+            CheckDisposed();
+            return NativeMethods.allpass_load_state(_handle, state, (UIntPtr)state.Length);
+        }
+
+        public bool SetInterpolation(InterpolationMode mode)
+        {
+            CheckDisposed();
+            return NativeMethods.allpass_set_interpolation(_handle, (uint)mode);
+        }
+
+        /// <summary>
+        /// 共有パラメータの最新値を読み込む（オーディオスレッドでブロックごとに呼ぶ）
+        /// </summary>
+        public bool ApplyParams(AllPassParams parameters)
+        {
+            CheckDisposed();
+            return NativeMethods.allpass_apply_params(_handle, parameters.Handle);
+        }
+
         private void CheckDisposed()
         {
             if (_disposed) throw new ObjectDisposedException(nameof(AllPassFilter));
         }
     }
+
+    /// <summary>
+    /// UIスレッドとオーディオスレッドで共有できるパラメータ
+    /// Set系のメソッドはロックなしでどのスレッドからでも呼べる
+    /// </summary>
+    public class AllPassParams : IDisposable
+    {
+        private static class NativeMethods
+        {
+            const string DllName = "allpass_filter";
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern IntPtr allpass_params_create();
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern void allpass_params_destroy(IntPtr parameters);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern void allpass_params_set_delay(IntPtr parameters, float delay);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern void allpass_params_set_gain(IntPtr parameters, float gain);
+
+            [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+            public static extern void allpass_params_set_smoothing(IntPtr parameters, float factor);
+        }
+
+        private IntPtr _handle;         // ネイティブオブジェクトのハンドル
+        private bool _disposed = false; // 破棄フラグ
+
+        public AllPassParams()
+        {
+            _handle = NativeMethods.allpass_params_create();
+        }
+
+        ~AllPassParams()
+        {
+            Dispose(false);
+        }
+
+        internal IntPtr Handle
+        {
+            get
+            {
+                CheckDisposed();
+                return _handle;
+            }
+        }
+
+        public void Dispose()
+        {
+            Dispose(true);
+            GC.SuppressFinalize(this);
+        }
+
+        protected virtual void Dispose(bool disposing)
+        {
+            if (!_disposed)
+            {
+                if (_handle != IntPtr.Zero)
+                {
+                    NativeMethods.allpass_params_destroy(_handle);
+                    _handle = IntPtr.Zero;
+                }
+
+                _disposed = true;
+            }
+        }
+
+        public void SetDelay(float delay)
+        {
+            CheckDisposed();
+            NativeMethods.allpass_params_set_delay(_handle, delay);
+        }
+
+        public void SetGain(float gain)
+        {
+            CheckDisposed();
+            NativeMethods.allpass_params_set_gain(_handle, gain);
+        }
+
+        public void SetSmoothing(float factor)
+        {
+            CheckDisposed();
+            NativeMethods.allpass_params_set_smoothing(_handle, factor);
+        }
+
+        private void CheckDisposed()
+        {
+            if (_disposed) throw new ObjectDisposedException(nameof(AllPassParams));
+        }
+    }
 }
\ No newline at end of file
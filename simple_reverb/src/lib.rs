@@ -1,26 +1,46 @@
 ﻿use nih_plug::prelude::*;
 use nih_plug::params::{FloatParam};
 use std::sync::Arc;
-use allpass_filter::{AllPassFilter, DelayLine, Linear};
+use allpass_filter::{AllPassFilter, DelayLine, DelayTime, DynamicInterpolator, Error, InterpolationMode, Linear, ParamEvent, ParamTarget};
+
+/// 遅延バッファをあらかじめ確保しておく最大のサンプルレート
+const MAX_SAMPLE_RATE: f32 = 192000.0;
 
 /// コムフィルタ
 /// 式: y[n] = x[n] + feedback * y[n - D]
 struct CombFilter {
     delay_line: DelayLine<f32, Linear>,
+    delay_time: DelayTime<f32>,
     delay_samples: f32,
     feedback: f32,
 }
 
 impl CombFilter {
     fn new(sample_rate: f32, delay_ms: f32, feedback: f32) -> Self {
-        let delay_samples = (sample_rate * (delay_ms / 1000.0)).round();
+        let delay_time = DelayTime::Millis(delay_ms);
+        let mut delay_line = DelayLine::new_with_time(delay_time, sample_rate, Linear);
+        delay_line.reserve_with_time(delay_time, MAX_SAMPLE_RATE);
         Self {
-            delay_line: DelayLine::new((delay_samples as usize) + 1000, Linear),
-            delay_samples,
+            delay_line,
+            delay_time,
+            delay_samples: delay_time.to_samples(sample_rate).round(),
             feedback,
         }
     }
 
+    /// サンプルレートを変更（確保済みのバッファ内で長さを合わせる）
+    /// `sample_rate`: サンプルレート（Hz）
+    /// 戻り値: 確保済みのバッファに収まらない場合はfalse
+    fn set_sample_rate(&mut self, sample_rate: f32) -> bool {
+        self.delay_samples = self.delay_time.to_samples(sample_rate).round();
+        self.delay_line.resize_with_time(self.delay_time, sample_rate).is_ok()
+    }
+
+    /// 遅延線をゼロで埋める
+    fn reset(&mut self) {
+        self.delay_line.reset();
+    }
+
     /// オーディオサンプルを処理
     /// `input`: 入力サンプル
     /// 戻り値: 出力サンプル
@@ -34,21 +54,22 @@ impl CombFilter {
 
 /// シュレーダー・リバーブ本体
 struct SchroederReverb {
-    combs: Vec<CombFilter>,                // 並列コムフィルタ
-    apfs: Vec<AllPassFilter<f32, Linear>>, // 直列オールパスフィルタ
+    combs: Vec<CombFilter>,                                  // 並列コムフィルタ
+    apfs: Vec<AllPassFilter<f32, DynamicInterpolator<f32>>>, // 直列オールパスフィルタ
+    event_error_logged: bool,                                // イベント反映の失敗をログ出力済みか
 }
 
 impl SchroederReverb {
-    fn new(sample_rate: f32) -> Self {
+    /// `sample_rate`: サンプルレート（Hz）
+    /// `diffusion`: オールパスフィルタのフィードバックゲイン
+    fn new(sample_rate: f32, diffusion: f32) -> Self {
         // 並列コムフィルタ
         let comb_params = [
             (29.7, 0.95), (37.1, 0.93), (41.1, 0.91), (43.7, 0.89)
         ];
 
         // 直列オールパスフィルタ
-        let apf_params = [
-            (5.0, 0.7), (1.7, 0.7)
-        ];
+        let apf_delays = [5.0, 1.7];
 
         // コムフィルタとオールパスフィルタの生成
         let combs = comb_params.iter()
@@ -56,38 +77,126 @@ impl SchroederReverb {
             .collect();
 
         // オールパスフィルタの生成
-        let apfs = apf_params.iter()
-            .map(|(ms, g)| {
-                let s = (sample_rate as f64 * (ms / 1000.0)) as f32;
-                AllPassFilter::new((s as usize) + 500, s, *g, Linear)
+        let apfs = apf_delays.iter()
+            .map(|ms| {
+                let interpolator = DynamicInterpolator::new(InterpolationMode::Linear);
+                let mut apf = AllPassFilter::new_with_time(sample_rate, DelayTime::Millis(*ms), DelayTime::Millis(*ms), diffusion, interpolator);
+                apf.reserve_with_time(DelayTime::Millis(*ms), MAX_SAMPLE_RATE);
+                apf
             })
             .collect();
 
-        Self { combs, apfs }
+        Self { combs, apfs, event_error_logged: false }
     }
 
-    /// オーディオサンプルを処理
-    /// `input`: 入力サンプル
-    /// 戻り値: 出力サンプル
-    fn process(&mut self, input: f32) -> f32 {
-        // コムフィルタを並列で処理
-        let mut wet = 0.0;
+    /// サンプルレートを変更（アロケーションを行わない）
+    /// `sample_rate`: サンプルレート（Hz）
+    /// 戻り値: 確保済みのバッファに収まらない場合はfalse
+    fn set_sample_rate(&mut self, sample_rate: f32) -> bool {
+        let mut fits = true;
         for comb in &mut self.combs {
-            wet += comb.process(input);
+            fits &= comb.set_sample_rate(sample_rate);
+        }
+        for apf in &mut self.apfs {
+            apf.set_sample_rate(sample_rate);
+        }
+        fits && sample_rate <= MAX_SAMPLE_RATE
+    }
+
+    /// 残響をすべて消去する
+    fn reset(&mut self) {
+        for comb in &mut self.combs {
+            comb.reset();
+        }
+        for apf in &mut self.apfs {
+            apf.reset();
+        }
+    }
+
+    /// オールパスフィルタの補間方法を切り替える
+    /// `mode`: 補間方法
+    fn set_interpolation_mode(&mut self, mode: InterpolationMode) {
+        for apf in &mut self.apfs {
+            apf.set_interpolation_mode(mode);
+        }
+    }
+
+    /// ブロック単位でオーディオサンプルをインプレース処理
+    /// `buffer`: 入力（モノラル）を受け取り、残響成分を書き込むスライス
+    /// `events`: オールパスフィルタに反映するパラメータ変更イベント（サンプル位置順）
+    fn process_block(&mut self, buffer: &mut [f32], events: &[ParamEvent<f32>]) {
+        // コムフィルタを並列で処理
+        for sample in buffer.iter_mut() {
+            let input = *sample;
+            let mut wet = 0.0;
+            for comb in &mut self.combs {
+                wet += comb.process(input);
+            }
+            *sample = wet;
         }
 
         // オールパスフィルタを直列で処理
         for apf in &mut self.apfs {
-            wet = apf.process(wet);
+            let Err(err) = apf.process_block_inplace_with_events(buffer, events) else {
+                continue;
+            };
+
+            // 不正なイベントは反映されずに処理されるため、目標値だけを直接設定して次のブロックから反映する
+            // ゲインがガードで拒否された場合は以前の値のまま処理を続ける
+            if err == Error::InvalidEvent {
+                for event in events {
+                    match event.target {
+                        ParamTarget::Delay(delay) => apf.set_delay_time(delay),
+                        ParamTarget::Gain(gain) => {
+                            let _ = apf.set_gain(gain);
+                        }
+                    }
+                }
+            }
+            if !self.event_error_logged {
+                self.event_error_logged = true;
+                nih_warn!("failed to apply allpass parameter events: {err:?}");
+            }
         }
 
-        wet * 0.2
+        for sample in buffer.iter_mut() {
+            *sample *= 0.2;
+        }
     }
 }
 
 pub struct MyReverb {
     params: Arc<MyReverbParams>,     // パラメータ
     reverb: Option<SchroederReverb>, // リバーブ本体
+    wet: Vec<f32>,                   // 残響成分の作業用バッファ（最大ブロック長）
+    diffusion: f32,                  // リバーブに反映済みの拡散量
+}
+
+/// ホストに公開する補間方法
+#[derive(Enum, Debug, PartialEq)]
+enum InterpolationParam {
+    #[name = "Nearest"]
+    Nearest,
+    #[name = "Linear"]
+    Linear,
+    #[name = "Cubic"]
+    Cubic,
+    #[name = "Sinc"]
+    Sinc,
+    #[name = "Thiran"]
+    Thiran,
+}
+
+impl InterpolationParam {
+    fn mode(&self) -> InterpolationMode {
+        match self {
+            InterpolationParam::Nearest => InterpolationMode::Nearest,
+            InterpolationParam::Linear => InterpolationMode::Linear,
+            InterpolationParam::Cubic => InterpolationMode::Cubic,
+            InterpolationParam::Sinc => InterpolationMode::Sinc,
+            InterpolationParam::Thiran => InterpolationMode::Thiran,
+        }
+    }
 }
 
 #[derive(Params)]
@@ -97,6 +206,12 @@ struct MyReverbParams {
 
     #[id = "gain"]
     pub gain: FloatParam,
+
+    #[id = "diffusion"]
+    pub diffusion: FloatParam,
+
+    #[id = "interpolation"]
+    pub interpolation: EnumParam<InterpolationParam>,
 }
 
 impl Default for MyReverb {
@@ -113,8 +228,16 @@ impl Default for MyReverb {
                     1.0,
                     FloatRange::Linear { min: 0.0, max: 1.0 },
                 ).with_smoother(SmoothingStyle::Linear(50.0)),
+                diffusion: FloatParam::new(
+                    "Diffusion",
+                    0.7,
+                    FloatRange::Linear { min: 0.0, max: 0.9 },
+                ),
+                interpolation: EnumParam::new("Interpolation", InterpolationParam::Linear),
             }),
             reverb: None,
+            wet: Vec::new(),
+            diffusion: 0.7,
         }
     }
 }
@@ -150,11 +273,27 @@ impl Plugin for MyReverb {
         buffer_config: &BufferConfig,
         _context: &mut impl InitContext<Self>,
     ) -> bool {
-        // サンプルレートに合わせてリバーブ生成
-        self.reverb = Some(SchroederReverb::new(buffer_config.sample_rate));
+        // 既存のリバーブは確保済みのバッファのままサンプルレートだけ変更し、収まらない場合は作り直す
+        let sample_rate = buffer_config.sample_rate;
+        let reused = match &mut self.reverb {
+            Some(reverb) => reverb.set_sample_rate(sample_rate),
+            None => false,
+        };
+        if !reused {
+            self.diffusion = self.params.diffusion.value();
+            self.reverb = Some(SchroederReverb::new(sample_rate, self.diffusion));
+        }
+        self.wet.resize(buffer_config.max_buffer_size as usize, 0.0);
         true
     }
 
+    fn reset(&mut self) {
+        // トランスポート停止後に古い残響が鳴り続けないように消去
+        if let Some(reverb) = &mut self.reverb {
+            reverb.reset();
+        }
+    }
+
     fn process(
         &mut self,
         buffer: &mut Buffer,
@@ -165,27 +304,50 @@ impl Plugin for MyReverb {
             Some(r) => r,
             None => return ProcessStatus::Normal,
         };
+        // 空のブロックにはイベントを置ける位置がないため、処理せずに次のブロックへ回す
+        if buffer.channels() == 0 || buffer.samples() == 0 {
+            return ProcessStatus::Normal;
+        }
 
-        for mut channel_samples in buffer.iter_samples() {
-            if channel_samples.len() == 0 {
-                continue;
-            }
-            let dry_wet = self.params.dry_wet.value();
-            let gain = self.params.gain.value();
+        // 補間方法はブロック単位で反映（切り替えはクロスフェードされる）
+        reverb.set_interpolation_mode(self.params.interpolation.value().mode());
+
+        // SAMPLE_ACCURATE_AUTOMATION によりパラメータが変わる位置でブロックが分割されるため、
+        // 変更はブロック先頭のイベントとして渡せば変化したサンプルから正確に反映される
+        let diffusion = self.params.diffusion.value();
+        let diffusion_event = [ParamEvent::gain(0, diffusion)];
+        let events: &[ParamEvent<f32>] = if diffusion != self.diffusion {
+            self.diffusion = diffusion;
+            &diffusion_event
+        } else {
+            &[]
+        };
 
-            // 左チャンネルの入力取得
+        // モノラル入力をまとめてリバーブ計算
+        let wet = &mut self.wet[..buffer.samples()];
+        for (wet_sample, mut channel_samples) in wet.iter_mut().zip(buffer.iter_samples()) {
+            // 左チャンネルの入力取得、右チャンネルがなければ左と同じにする
             let in_l = *channel_samples.get_mut(0).unwrap();
-
-            // 右チャンネルがあれば取得、なければ左と同じにする
             let in_r = if channel_samples.len() > 1 {
                 *channel_samples.get_mut(1).unwrap()
             } else {
                 in_l
             };
+            *wet_sample = (in_l + in_r) * 0.5;
+        }
+        reverb.process_block(wet, events);
 
-            // モノラル入力としてリバーブ計算
-            let mono_in = (in_l + in_r) * 0.5;
-            let wet_signal = reverb.process(mono_in);
+        for (&wet_signal, mut channel_samples) in wet.iter().zip(buffer.iter_samples()) {
+            // 平滑化された値をサンプルごとに進める
+            let dry_wet = self.params.dry_wet.smoothed.next();
+            let gain = self.params.gain.smoothed.next();
+
+            let in_l = *channel_samples.get_mut(0).unwrap();
+            let in_r = if channel_samples.len() > 1 {
+                *channel_samples.get_mut(1).unwrap()
+            } else {
+                in_l
+            };
 
             // ステレオ出力
             let out_l = (in_l * (1.0 - dry_wet)) + (wet_signal * dry_wet);